@@ -2,18 +2,43 @@
 use switchboard_v2::VrfAccountData;
 use spl_associated_token_account::*;
 use anchor_lang::prelude::*;
-use anchor_spl::token::*;
+use spl_token_2022::extension::{StateWithExtensions, ExtensionType};
+use spl_token_2022::extension::default_account_state::DefaultAccountState;
 use std::mem::size_of;
+use solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use solana_program::system_instruction::MAX_PERMITTED_DATA_LENGTH;
 
 declare_id!("4iMPsUWtpnNQhjhs1gSw74j5arJpxXa6DrUWYKXsgWVn");
 
 const NULL_KEY_ARRAY: [u8; 32] = [0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0];
 
 const MAX_RAFFLE_LENGTH: u64 = 94670856; // 3 Years
-const NOT_REDEEMED_LENGTH: u64 = 15778476; // 6 Months
-const MAX_MASTER_RAFFLE_INDEXES: u16 = 5000; // Too many will overflow the stack
+const MIN_CLAIM_WINDOW: u64 = 86400; // 1 Day
+const MAX_CLAIM_WINDOW: u64 = 31556952; // 1 Year
+const MIN_REROLL_WINDOW: u64 = 3600; // 1 Hour
+const MIN_END_DATE_NOTICE: u64 = 86400; // 1 Day
+const MAX_BPS: u16 = 10000; // 100%
+const MAX_MASTER_RAFFLE_INDEXES: u16 = 300; // PDAs are created through a CPI, which caps them at 10KB
 const MAX_NAME_LENGTH: usize = 63; 
+const MAX_PRICE_STEPS: usize = 4; // Every payment option is sized for this many
+const MAX_PAYEES: usize = 8; // Every raffle is sized for this many
 const STATE_SEED: &[u8] = b"SOLAPE";
+const MASTER_RAFFLE_SEED: &[u8] = b"MASTER_RAFFLE";
+const RAFFLE_SEED: &[u8] = b"RAFFLE";
+const JACKPOT_SEED: &[u8] = b"JACKPOT";
+const TEMPLATE_SEED: &[u8] = b"TEMPLATE";
+const VOUCHER_SEED: &[u8] = b"VOUCHER";
+const VOUCHERS_PER_BITMAP: u64 = 8192; // One bit per nonce, 1KB per bitmap
+
+// Metaplex programs that programmable NFTs are moved through
+pub mod token_metadata {
+    use super::*;
+    declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+}
+pub mod token_auth_rules {
+    use super::*;
+    declare_id!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
+}
 
 #[program]
 pub mod raffle {
@@ -42,9 +67,20 @@ pub mod raffle {
         master_raffle.name = String::from(params.name);
         master_raffle.master_raffle = master_raffle.key();
         master_raffle.owner = ctx.accounts.owner.key();
+        master_raffle.index = params.index;
 
         // Limits
         master_raffle.max_raffles = params.max_raffles;
+        master_raffle.raffle_counter = 0;
+        master_raffle.paused = false;
+        master_raffle.operator = Pubkey::new_from_array(NULL_KEY_ARRAY).clone();
+        master_raffle.voucher_authority = Pubkey::new_from_array(NULL_KEY_ARRAY).clone();
+
+        // Jackpot
+        master_raffle.jackpot_mint = Pubkey::new_from_array(NULL_KEY_ARRAY).clone();
+        master_raffle.jackpot_bps = 0;
+        master_raffle.jackpot_odds_bps = 0;
+        master_raffle.jackpot_balance = 0;
 
         // RNG
         master_raffle.oracle = ctx.accounts.oracle.key.clone();
@@ -120,6 +156,79 @@ pub mod raffle {
     }
 
 
+    // ------------ PAUSE MASTER RAFFLE -------------------------------
+    pub fn pause_master_raffle(
+        ctx: Context<PauseMasterRaffle>,
+        params: PauseMasterRaffleParams,
+    ) -> ProgramResult {
+
+        let master_raffle = &mut ctx.accounts.master_raffle;
+
+        // Emergency switch, halts ticket sales in every child raffle
+        master_raffle.paused = params.paused;
+
+        emit!(MasterRafflePaused {
+            master_raffle: master_raffle.key(),
+            paused: master_raffle.paused,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+
+        Ok(())
+    }
+
+    // ------------ CONFIGURE JACKPOT -------------------------------
+    pub fn configure_jackpot(
+        ctx: Context<ConfigureJackpot>,
+        params: ConfigureJackpotParams,
+    ) -> ProgramResult {
+
+        let master_raffle = &mut ctx.accounts.master_raffle;
+
+        // Simple Checks
+        if params.jackpot_bps > MAX_BPS { return Err(ErrorCode::BadJackpotShare.into()); }
+        if params.jackpot_odds_bps > MAX_BPS { return Err(ErrorCode::BadJackpotOdds.into()); }
+
+        // Check Jackpot Keeper
+        let (jackpot_keeper, jackpot_nonce) = Pubkey::find_program_address(
+            &[JACKPOT_SEED, master_raffle.to_account_info().key.as_ref()],
+            ctx.program_id,
+        );
+
+        if &jackpot_keeper != ctx.accounts.jackpot_keeper.to_account_info().key {
+            return Err(ErrorCode::BadJackpotVault.into());
+        }
+
+        // The mint can only change once the jackpot has been won
+        let jackpot_mint = ctx.accounts.jackpot_mint.key();
+        if master_raffle.jackpot_mint != jackpot_mint && master_raffle.jackpot_balance > 0 {
+            return Err(ErrorCode::JackpotNotEmpty.into());
+        }
+        get_mint_decimals(&ctx.accounts.jackpot_mint)?;
+
+        master_raffle.jackpot_keeper = jackpot_keeper;
+        master_raffle.jackpot_nonce = jackpot_nonce;
+        master_raffle.jackpot_mint = jackpot_mint;
+        master_raffle.jackpot_bps = params.jackpot_bps;
+        master_raffle.jackpot_odds_bps = params.jackpot_odds_bps;
+
+        Ok(())
+    }
+
+    // ------------ UPDATE OPERATOR -------------------------------
+    pub fn update_operator(
+        ctx: Context<UpdateOperator>,
+        params: UpdateOperatorParams,
+    ) -> ProgramResult {
+
+        let master_raffle = &mut ctx.accounts.master_raffle;
+
+        // A 0'd key removes the operator or the voucher authority
+        master_raffle.operator = params.operator;
+        master_raffle.voucher_authority = params.voucher_authority;
+
+        Ok(())
+    }
+
     // ------------ CREATE RAFFLE -------------------------------
     pub fn create_raffle(
         ctx: Context<CreateRaffle>,
@@ -135,45 +244,52 @@ pub mod raffle {
         if params.payment_option_count < 1 { return Err(ErrorCode::GeneralError.into()); }
         if params.reward_count < 1 { return Err(ErrorCode::NeedReward.into()); }
         if params.max_holder_count < 1 { return Err(ErrorCode::NeedHolders.into()); }
+        if params.claim_window < MIN_CLAIM_WINDOW || params.claim_window > MAX_CLAIM_WINDOW { return Err(ErrorCode::BadClaimWindow.into()); }
+        if params.max_rerolls > 0 && (params.reroll_window < MIN_REROLL_WINDOW || params.reroll_window >= params.claim_window) { return Err(ErrorCode::BadRerollWindow.into()); }
+        if get_raffle_size(params.clone()) > MAX_PERMITTED_DATA_LENGTH as usize { return Err(ErrorCode::RaffleTooBig.into()); }
         if master_raffle.raffles.len() + 1 > master_raffle.max_raffles as usize { return Err(ErrorCode::TooManyRaffles.into()); }
         if bot.request_counter < 1 { return Err(ErrorCode::NeedRngFirst.into()); }
 
         // Check Gatekeeper
-        let gatekeeper = Pubkey::create_program_address(
-            &[raffle.to_account_info().key.as_ref(), &[params.nonce]],
+        let (gatekeeper, nonce) = Pubkey::find_program_address(
+            &[raffle.to_account_info().key.as_ref()],
             ctx.program_id,
-        )
-        .map_err(|_| ErrorCode::BadGatekeeperNonce)?;
+        );
 
         if &gatekeeper != ctx.accounts.gatekeeper.to_account_info().key {
-            return Err(ErrorCode::BadGatekeeperNonce.into());
+            return Err(ErrorCode::BadGatekeeper.into());
         }
 
-        // Authorities
-        raffle.name = String::from(params.name);
-        raffle.raffle = raffle.key();
-        raffle.master_raffle = master_raffle.key();
-        raffle.owner = ctx.accounts.owner.key();
-        raffle.gatekeeper = ctx.accounts.gatekeeper.key();
-        raffle.nonce = params.nonce;
+        init_raffle(
+            raffle,
+            master_raffle,
+            ctx.accounts.owner.key(),
+            gatekeeper,
+            nonce,
+            &params,
+        );
 
-        // Limits
-        raffle.max_tickets_per_holder = params.max_tickets_per_holder;
-        raffle.payment_option_count = params.payment_option_count;
-        raffle.reward_count =params.reward_count;
-        raffle.max_holder_count = params.max_holder_count;
+        Ok(())
+    }
 
-        // Rules
-        raffle.start_date = 0;
-        raffle.end_date = !0;
+    // ------------ EXPAND RAFFLE -------------------------------
+    pub fn expand_raffle(
+        ctx: Context<ExpandRaffle>,
+    ) -> ProgramResult {
 
-        // RNG
-        raffle.rng_bot = master_raffle.rng_bot.key();
+        let raffle = &ctx.accounts.raffle;
+        let raffle_info = raffle.to_account_info();
+        let raffle_size = get_raffle_size(raffle.get_create_raffle_params());
 
-        // Master Raffle
-        master_raffle.raffles.push(raffle.key());
+        // Accounts can only grow by 10KB per instruction, call until the raffle is as big as it was created for
+        if raffle_info.data_len() >= raffle_size { return Ok(()); }
 
-        Ok(())
+        grow_raffle_account(
+            &raffle_info,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program,
+            std::cmp::min(raffle_size - raffle_info.data_len(), MAX_PERMITTED_DATA_INCREASE),
+        )
     }
 
     // ------------ LOAD PAYMENT OPTION -------------------------------
@@ -188,16 +304,24 @@ pub mod raffle {
         if params.tickets_per_payment > raffle.max_tickets_per_holder { return Err(ErrorCode::TooManyTicketsPerPayment.into()); }
         if params.payment_amount < 1  { return Err(ErrorCode::NeedPaymentAmount.into()); }
         if params.tickets_per_payment < 1  { return Err(ErrorCode::NeedTicketsPerPayemnt.into()); }
+        if params.weight_bps < 1  { return Err(ErrorCode::NeedTicketWeight.into()); }
         if raffle.start_date != 0  { return Err(ErrorCode::RaffleStarted.into()); }
+        check_price_schedule(&params)?;
         if raffle.payment_option_count < (raffle.payment_options.len() + 1) as u8 { return Err(ErrorCode::TooManyPaymentOptions.into()); }
+        get_mint_decimals(&ctx.accounts.payment_mint)?;
 
         raffle.payment_options.push(
             TicketPaymentOption{
                 name: String::from(params.name),
-                payment_mint: ctx.accounts.payment_vault.mint,
+                payment_mint: ctx.accounts.payment_mint.key(),
                 payment_amount: params.payment_amount,
                 tickets_per_payment: params.tickets_per_payment,
+                weight_bps: params.weight_bps,
+                end_payment_amount: params.end_payment_amount,
+                price_steps: params.price_steps.clone(),
+                max_purchases: params.max_purchases,
                 payment_tally: 0,
+                amount_paid: 0,
                 cashed_out: false,
             }
         );
@@ -216,20 +340,72 @@ pub mod raffle {
         if params.name.len() > MAX_NAME_LENGTH { return Err(ErrorCode::NameTooLong.into()); }
         if raffle.start_date != 0  { return Err(ErrorCode::RaffleStarted.into()); }
         if params.reward_amount < 1  { return Err(ErrorCode::NeedRewardAmount.into()); }
-        if params.reward_amount > ctx.accounts.owner_vault.amount { return Err(ErrorCode::BadOwnerRewardBalance.into()); }
+        let owner_vault = get_token_account(&ctx.accounts.owner_vault)?;
+        if params.reward_amount > owner_vault.amount { return Err(ErrorCode::BadOwnerRewardBalance.into()); }
+        if owner_vault.is_frozen() { return Err(ErrorCode::FrozenRewardVault.into()); } // Programmable NFTs stay frozen, they go through load_pnft_reward
         if raffle.reward_count < (raffle.rewards.len() + 1) as u8 { return Err(ErrorCode::TooManyRewards.into()); }
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.owner_vault.to_account_info().clone(),
-            to: ctx.accounts.reward_vault.to_account_info().clone(),
-            authority: ctx.accounts.owner.to_account_info().clone(),
-        };
-        let cpi_program = ctx.accounts.token_program.clone();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        let amount_before = get_token_account(&ctx.accounts.reward_vault)?.amount;
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.owner_vault,
+            &ctx.accounts.reward_mint,
+            &ctx.accounts.reward_vault,
+            &ctx.accounts.owner.to_account_info(),
+            params.reward_amount,
+            &[],
+        )?;
+
+        raffle.rewards.push(
+            Reward{
+                name: String::from(params.name),
+                group_id: params.group_id.clone(),
+                reward_amount: get_received_amount(&ctx.accounts.reward_vault, amount_before)?,
+                reward_mint: ctx.accounts.reward_mint.key(),
+                winner: Pubkey::new_from_array(NULL_KEY_ARRAY).clone(),
+                reward_redeemed: false,
+                sponsor: ctx.accounts.owner.key(),
+                kind: RewardKind::Spl,
+                pot_share_bps: 0,
+                picked_at: 0,
+                rerolls: 0,
+                tier: params.tier,
+            }
+        );
+
+        Ok(())
+    }
 
-        let token_tx_result = transfer(cpi_ctx, params.reward_amount);
+    // ------------ LOAD SOL REWARD -------------------------------
+    pub fn load_sol_reward(
+        ctx: Context<LoadSolReward>,
+        params: LoadRewardParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let gatekeeper = &ctx.accounts.gatekeeper;
+
+        if params.name.len() > MAX_NAME_LENGTH { return Err(ErrorCode::NameTooLong.into()); }
+        if raffle.start_date != 0  { return Err(ErrorCode::RaffleStarted.into()); }
+        if params.reward_amount < 1  { return Err(ErrorCode::NeedRewardAmount.into()); }
+        if raffle.reward_count < (raffle.rewards.len() + 1) as u8 { return Err(ErrorCode::TooManyRewards.into()); }
+
+        // The gatekeeper holds the SOL, keep it rent exempt so payouts never leave it short
+        let rent_reserve = Rent::get()?.minimum_balance(0);
+        let amount = params.reward_amount + rent_reserve.saturating_sub(gatekeeper.lamports());
+
+        if amount > ctx.accounts.owner.lamports() { return Err(ErrorCode::BadOwnerRewardBalance.into()); }
+
+        let sol_tx_result = solana_program::program::invoke(
+            &solana_program::system_instruction::transfer(ctx.accounts.owner.key, gatekeeper.key, amount),
+            &[
+                ctx.accounts.owner.to_account_info().clone(),
+                gatekeeper.clone(),
+                ctx.accounts.system_program.clone(),
+            ],
+        );
 
-        if !token_tx_result.is_ok() {
+        if !sol_tx_result.is_ok() {
             return Err(ErrorCode::CouldNotTX.into());
         }
 
@@ -238,15 +414,218 @@ pub mod raffle {
                 name: String::from(params.name),
                 group_id: params.group_id.clone(),
                 reward_amount: params.reward_amount,
-                reward_mint: ctx.accounts.reward_vault.mint.key(),
+                reward_mint: Pubkey::new_from_array(NULL_KEY_ARRAY).clone(),
+                winner: Pubkey::new_from_array(NULL_KEY_ARRAY).clone(),
+                reward_redeemed: false,
+                sponsor: ctx.accounts.owner.key(),
+                kind: RewardKind::Lamports,
+                pot_share_bps: 0,
+                picked_at: 0,
+                rerolls: 0,
+                tier: params.tier,
+            }
+        );
+
+        Ok(())
+    }
+
+    // ------------ LOAD POT REWARD -------------------------------
+    pub fn load_pot_reward(
+        ctx: Context<LoadPotReward>,
+        params: LoadPotRewardParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+
+        if params.name.len() > MAX_NAME_LENGTH { return Err(ErrorCode::NameTooLong.into()); }
+        if raffle.start_date != 0  { return Err(ErrorCode::RaffleStarted.into()); }
+        if params.pot_share_bps < 1 { return Err(ErrorCode::NeedPotShare.into()); }
+        if raffle.reward_count < (raffle.rewards.len() + 1) as u8 { return Err(ErrorCode::TooManyRewards.into()); }
+
+        // The pot has to be paid into with a payment option
+        let mut has_payment_option = false;
+        for i in 0..raffle.payment_options.len() {
+            if raffle.payment_options[i].payment_mint == params.payment_mint {
+                has_payment_option = true;
+                break;
+            }
+        }
+        if !has_payment_option { return Err(ErrorCode::BadPaymentMint.into()); }
+
+        // Shares of the same pot can not add up to more than all of it
+        let mut pot_share_bps = params.pot_share_bps as u32;
+        for i in 0..raffle.rewards.len() {
+            if raffle.rewards[i].kind == RewardKind::PotShare && raffle.rewards[i].reward_mint == params.payment_mint {
+                pot_share_bps += raffle.rewards[i].pot_share_bps as u32;
+            }
+        }
+        if pot_share_bps > MAX_BPS as u32 { return Err(ErrorCode::BadPotShare.into()); }
+
+        raffle.rewards.push(
+            Reward{
+                name: String::from(params.name),
+                group_id: params.group_id.clone(),
+                reward_amount: 0,
+                reward_mint: params.payment_mint,
+                winner: Pubkey::new_from_array(NULL_KEY_ARRAY).clone(),
+                reward_redeemed: false,
+                sponsor: ctx.accounts.owner.key(),
+                kind: RewardKind::PotShare,
+                pot_share_bps: params.pot_share_bps,
+                picked_at: 0,
+                rerolls: 0,
+                tier: params.tier,
+            }
+        );
+
+        Ok(())
+    }
+
+    // ------------ LOAD JACKPOT REWARD -------------------------------
+    pub fn load_jackpot_reward(
+        ctx: Context<LoadJackpotReward>,
+        params: LoadJackpotRewardParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let master_raffle = &ctx.accounts.master_raffle;
+
+        if params.name.len() > MAX_NAME_LENGTH { return Err(ErrorCode::NameTooLong.into()); }
+        if raffle.start_date != 0  { return Err(ErrorCode::RaffleStarted.into()); }
+        if master_raffle.jackpot_keeper == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::NoJackpot.into()); }
+        if raffle.reward_count < (raffle.rewards.len() + 1) as u8 { return Err(ErrorCode::TooManyRewards.into()); }
+
+        // One shot at the jackpot per raffle
+        for i in 0..raffle.rewards.len() {
+            if raffle.rewards[i].kind == RewardKind::Jackpot { return Err(ErrorCode::TooManyRewards.into()); }
+        }
+
+        raffle.rewards.push(
+            Reward{
+                name: String::from(params.name),
+                group_id: Pubkey::new_from_array(NULL_KEY_ARRAY).clone(),
+                reward_amount: 0,
+                reward_mint: master_raffle.jackpot_mint,
                 winner: Pubkey::new_from_array(NULL_KEY_ARRAY).clone(),
                 reward_redeemed: false,
+                sponsor: ctx.accounts.owner.key(),
+                kind: RewardKind::Jackpot,
+                pot_share_bps: 0,
+                picked_at: 0,
+                rerolls: 0,
+                tier: params.tier,
             }
         );
 
         Ok(())
     }
 
+    // ------------ UPDATE SPONSORS -------------------------------
+    pub fn update_sponsors(
+        ctx: Context<UpdateSponsors>,
+        params: UpdateSponsorsParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+
+        if params.sponsors.len() > raffle.max_sponsor_count as usize { return Err(ErrorCode::TooManySponsors.into()); }
+
+        raffle.sponsors = params.sponsors;
+
+        Ok(())
+    }
+
+    // ------------ UPDATE PAYEES -------------------------------
+    pub fn update_payees(
+        ctx: Context<UpdatePayees>,
+        params: UpdatePayeesParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+
+        // Locked in once tickets can be bought, so buyers know where the money goes
+        if raffle.start_date != 0  { return Err(ErrorCode::RaffleStarted.into()); }
+        check_payees(&params.payees, &params.dust_recipient)?;
+
+        raffle.payees = params.payees;
+        raffle.dust_recipient = params.dust_recipient;
+
+        Ok(())
+    }
+
+    // ------------ ADD REWARD -------------------------------
+    pub fn add_reward(
+        ctx: Context<AddReward>,
+        params: LoadRewardParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let sponsor = ctx.accounts.sponsor.key();
+
+        // Simple Checks
+        if params.name.len() > MAX_NAME_LENGTH { return Err(ErrorCode::NameTooLong.into()); }
+        if raffle.start_date == 0 { return Err(ErrorCode::RaffleNotStarted.into()); }
+        if Clock::get()?.unix_timestamp as u64 > raffle.end_date { return Err(ErrorCode::RaffleEnded.into()); }
+        if params.reward_amount < 1  { return Err(ErrorCode::NeedRewardAmount.into()); }
+        let sponsor_vault = get_token_account(&ctx.accounts.sponsor_vault)?;
+        if params.reward_amount > sponsor_vault.amount { return Err(ErrorCode::BadOwnerRewardBalance.into()); }
+        if sponsor_vault.is_frozen() { return Err(ErrorCode::FrozenRewardVault.into()); }
+        if sponsor != raffle.owner && !raffle.sponsors.contains(&sponsor) { return Err(ErrorCode::BadSponsor.into()); }
+
+        // Make room for the reward if the raffle was sized without it
+        if raffle.reward_count < (raffle.rewards.len() + 1) as u8 {
+            if raffle.reward_count == u8::MAX { return Err(ErrorCode::TooManyRewards.into()); }
+
+            grow_raffle_account(
+                &raffle.to_account_info(),
+                &ctx.accounts.sponsor.to_account_info(),
+                &ctx.accounts.system_program,
+                size_of::<Reward>() + (MAX_NAME_LENGTH + 1),
+            )?;
+            raffle.reward_count += 1;
+        }
+
+        // Sponsors send their tokens straight to the gatekeeper
+        let amount_before = get_token_account(&ctx.accounts.reward_vault)?.amount;
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.sponsor_vault,
+            &ctx.accounts.reward_mint,
+            &ctx.accounts.reward_vault,
+            &ctx.accounts.sponsor.to_account_info(),
+            params.reward_amount,
+            &[],
+        )?;
+
+        // Rewards can only ever be appended, so buyers are never worse off
+        raffle.rewards.push(
+            Reward{
+                name: String::from(params.name),
+                group_id: params.group_id.clone(),
+                reward_amount: get_received_amount(&ctx.accounts.reward_vault, amount_before)?,
+                reward_mint: ctx.accounts.reward_mint.key(),
+                winner: Pubkey::new_from_array(NULL_KEY_ARRAY).clone(),
+                reward_redeemed: false,
+                sponsor: sponsor,
+                kind: RewardKind::Spl,
+                pot_share_bps: 0,
+                picked_at: 0,
+                rerolls: 0,
+                tier: params.tier,
+            }
+        );
+
+        emit!(RewardAdded {
+            raffle: raffle.key(),
+            reward_index: (raffle.rewards.len() - 1) as u8,
+            sponsor: sponsor,
+            reward_mint: ctx.accounts.reward_mint.key(),
+            reward_amount: raffle.rewards[raffle.rewards.len() - 1].reward_amount,
+        });
+
+        Ok(())
+    }
+
     // ------------ START RAFFLE -------------------------------
     pub fn start_raffle(
         ctx: Context<StartRaffle>,
@@ -254,18 +633,121 @@ pub mod raffle {
     ) -> ProgramResult {
 
         let raffle = &mut ctx.accounts.raffle;
-        let start_date = Clock::get()?.unix_timestamp as u64;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let start_date = params.start_date.unwrap_or(current_time);
 
         // Simple Checkts
+        if start_date < current_time { return Err(ErrorCode::BadStartDate.into()); }
+        if start_date > current_time + MAX_RAFFLE_LENGTH { return Err(ErrorCode::TooBigStartDate.into()); }
         if params.end_date <= start_date { return Err(ErrorCode::BadEndDate.into()); }
         if raffle.start_date != 0  { return Err(ErrorCode::RaffleStarted.into()); }
         if params.end_date > start_date + MAX_RAFFLE_LENGTH { return Err(ErrorCode::TooBigEndDate.into()); }
         if raffle.payment_options.len() < 1 { return Err(ErrorCode::NeedPaymentOption.into()); }
         if raffle.rewards.len() < 1 { return Err(ErrorCode::NeedReward.into()); }
+        if raffle.to_account_info().data_len() < get_raffle_size(raffle.get_create_raffle_params()) { return Err(ErrorCode::RaffleNotExpanded.into()); }
 
-        // Rules
+        // Rules - setting the start date locks the configuration, even if it is in the future
         raffle.start_date = start_date;
         raffle.end_date = params.end_date;
+        raffle.ramp_end_date = params.end_date;
+
+        Ok(())
+    }
+
+    // ------------ UPDATE END DATE -------------------------------
+    pub fn update_end_date(
+        ctx: Context<UpdateEndDate>,
+        params: UpdateEndDateParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let old_end_date = raffle.end_date;
+
+        // Simple Checks
+        if raffle.start_date == 0 { return Err(ErrorCode::RaffleNotStarted.into()); }
+        if current_time > raffle.end_date { return Err(ErrorCode::RaffleEnded.into()); }
+        if params.end_date <= current_time || params.end_date <= raffle.start_date { return Err(ErrorCode::BadEndDate.into()); }
+        if params.end_date > raffle.start_date + MAX_RAFFLE_LENGTH { return Err(ErrorCode::TooBigEndDate.into()); }
+        if params.end_date == old_end_date { return Err(ErrorCode::SameEndDate.into()); }
+
+        // Shortening is only allowed before anyone has bought in, or with notice
+        if params.end_date < old_end_date && raffle.holders.len() > 0 {
+            if params.end_date < current_time + MIN_END_DATE_NOTICE { return Err(ErrorCode::NeedEndDateNotice.into()); }
+        }
+
+        // Rules
+        raffle.end_date = params.end_date;
+        raffle.end_date_updates += 1;
+
+        emit!(EndDateUpdated {
+            raffle: raffle.key(),
+            old_end_date: old_end_date,
+            new_end_date: raffle.end_date,
+            end_date_updates: raffle.end_date_updates,
+        });
+
+        Ok(())
+    }
+
+    // ------------ PAUSE RAFFLE -------------------------------
+    pub fn pause_raffle(
+        ctx: Context<PauseRaffle>,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Simple Checks
+        if raffle.start_date == 0 { return Err(ErrorCode::RaffleNotStarted.into()); }
+        if current_time > raffle.end_date { return Err(ErrorCode::RaffleEnded.into()); }
+        if raffle.paused_at != 0 { return Err(ErrorCode::RafflePaused.into()); }
+
+        // Rules
+        raffle.paused_at = current_time;
+        raffle.pause_count += 1;
+
+        emit!(RafflePaused {
+            raffle: raffle.key(),
+            paused_at: raffle.paused_at,
+            pause_count: raffle.pause_count,
+        });
+
+        Ok(())
+    }
+
+    // ------------ RESUME RAFFLE -------------------------------
+    pub fn resume_raffle(
+        ctx: Context<ResumeRaffle>,
+        params: ResumeRaffleParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Simple Checks
+        if raffle.paused_at == 0 { return Err(ErrorCode::RaffleNotPaused.into()); }
+
+        let paused_duration = current_time - raffle.paused_at;
+
+        // Give buyers back the time they lost, without going past the max length
+        if params.extend_end_date {
+            raffle.end_date = std::cmp::min(
+                raffle.end_date + paused_duration,
+                raffle.start_date + MAX_RAFFLE_LENGTH,
+            );
+        }
+
+        // Rules
+        raffle.paused_at = 0;
+        raffle.total_paused_duration += paused_duration;
+
+        emit!(RaffleResumed {
+            raffle: raffle.key(),
+            resumed_at: current_time,
+            paused_duration: paused_duration,
+            end_date: raffle.end_date,
+        });
 
         Ok(())
     }
@@ -311,14 +793,17 @@ pub mod raffle {
     ) -> ProgramResult {
 
         let raffle = &mut ctx.accounts.raffle;
+        let current_time = Clock::get()?.unix_timestamp as u64;
 
         // Simple Checks
         if params.payment_option_index as usize >= raffle.payment_options.len() { return Err(ErrorCode::BadPaymentIndex.into()); }
-        if raffle.start_date == 0 { return Err(ErrorCode::RaffleNotStarted.into()); }
-        if Clock::get()?.unix_timestamp as u64 > raffle.end_date { return Err(ErrorCode::RaffleEnded.into()); }
+        if raffle.start_date == 0 || current_time < raffle.start_date { return Err(ErrorCode::RaffleNotStarted.into()); }
+        if current_time > raffle.end_date { return Err(ErrorCode::RaffleEnded.into()); }
+        if raffle.paused_at != 0 || ctx.accounts.master_raffle.paused { return Err(ErrorCode::RafflePaused.into()); }
         if ctx.accounts.holder.key == &raffle.owner { return Err(ErrorCode::BadBuyer.into()); }
 
         let payment_option = raffle.payment_options[params.payment_option_index as usize].clone();
+        let payment_amount = get_payment_amount(&payment_option, raffle.start_date, raffle.ramp_end_date, current_time);
         let mut holder_index = raffle.holders.len();
 
         // Check for existing holder
@@ -329,9 +814,13 @@ pub mod raffle {
             }
         }
 
+        // Supply Checks
+        if payment_option.max_purchases != 0 && payment_option.payment_tally >= payment_option.max_purchases { return Err(ErrorCode::PaymentOptionSoldOut.into()); }
+        if raffle.max_total_tickets != 0 && raffle.tickets_sold + payment_option.tickets_per_payment as u32 > raffle.max_total_tickets { return Err(ErrorCode::RaffleSoldOut.into()); }
+
         // Payment Checks
-        if payment_option.payment_mint != ctx.accounts.holder_vault.mint { return Err(ErrorCode::BadPaymentMint.into()); }
-        if payment_option.payment_amount > ctx.accounts.holder_vault.amount { return Err(ErrorCode::NotEnoughToBuy.into()); }
+        if payment_option.payment_mint != ctx.accounts.payment_mint.key() { return Err(ErrorCode::BadPaymentMint.into()); }
+        if payment_amount > get_token_account(&ctx.accounts.holder_vault)?.amount { return Err(ErrorCode::NotEnoughToBuy.into()); }
 
         // Last Checks
         if holder_index == raffle.holders.len() { // Is not in holders
@@ -344,75 +833,212 @@ pub mod raffle {
         }
 
         // Grab Payment
-        let cpi_program = ctx.accounts.token_program.clone();
-        let rx = Transfer {
-            from: ctx.accounts.holder_vault.to_account_info().clone(),
-            to: ctx.accounts.payment_vault.to_account_info().clone(),
-            authority: ctx.accounts.holder.to_account_info().clone(),
-        };
-        let rx_cpi = CpiContext::new(cpi_program.clone(), rx);
-
-        let rx_result = transfer(rx_cpi, payment_option.payment_amount);
-
-        if !rx_result.is_ok() {
-            return Err(ErrorCode::CouldNotTX.into());
-        }
+        let amount_before = get_token_account(&ctx.accounts.payment_vault)?.amount;
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.holder_vault,
+            &ctx.accounts.payment_mint,
+            &ctx.accounts.payment_vault,
+            &ctx.accounts.holder.to_account_info(),
+            payment_amount,
+            &[],
+        )?;
+        let amount_received = get_received_amount(&ctx.accounts.payment_vault, amount_before)?;
 
         // Set State
+        let entries = payment_option.tickets_per_payment as u32 * payment_option.weight_bps as u32;
         if holder_index == raffle.holders.len() { // Is not in holders
             raffle.holders.push(
                 TicketHolder{
                     holder: ctx.accounts.holder.key().clone(),
-                    tickets: payment_option.tickets_per_payment,
+                    tickets: 0,
+                    entries: 0,
+                    option_tickets: Vec::new(),
                 }
             );
-        } else {
-            raffle.holders[holder_index].tickets += payment_option.tickets_per_payment;
         }
 
+        // Holders granted tickets before they bought any have no per option counts yet
+        let payment_option_count = raffle.payment_options.len();
+        raffle.holders[holder_index].option_tickets.resize(payment_option_count, 0);
+
+        raffle.holders[holder_index].tickets += payment_option.tickets_per_payment;
+        raffle.holders[holder_index].entries += entries;
+        raffle.holders[holder_index].option_tickets[params.payment_option_index as usize] += payment_option.tickets_per_payment;
+
         raffle.payment_options[params.payment_option_index as usize].payment_tally += 1;
+        raffle.payment_options[params.payment_option_index as usize].amount_paid += amount_received;
+        raffle.tickets_sold += payment_option.tickets_per_payment as u32;
 
         Ok(())
     }
 
-
-    // ------------ PICK WINNER -------------------------------
-    pub fn pick_winner(
-        ctx: Context<PickWinner>,
+    // ------------ GRANT TICKETS --------------------------------
+    pub fn grant_tickets(
+        ctx: Context<GrantTickets>,
+        params: GrantTicketsParams
     ) -> ProgramResult {
 
         let raffle = &mut ctx.accounts.raffle;
-        let bot = &mut ctx.accounts.rng_bot.load_mut()?;
-
-        let mut reward_index = raffle.rewards.len();
-        let null_winner = Pubkey::new_from_array(NULL_KEY_ARRAY).clone();
+        let master_raffle = &ctx.accounts.master_raffle;
+        let authority = ctx.accounts.authority.key();
+        let current_time = Clock::get()?.unix_timestamp as u64;
 
-        for i in 0..raffle.rewards.len() {
-            if raffle.rewards[i].winner == null_winner {
-                reward_index = i;
-                break;
-            }
+        // Simple Checks
+        if authority != raffle.owner && authority != master_raffle.operator { return Err(ErrorCode::BadOperator.into()); }
+        if raffle.start_date == 0 || current_time < raffle.start_date { return Err(ErrorCode::RaffleNotStarted.into()); }
+        if current_time > raffle.end_date { return Err(ErrorCode::RaffleEnded.into()); }
+        if raffle.paused_at != 0 || master_raffle.paused { return Err(ErrorCode::RafflePaused.into()); }
+
+        let mut granted = 0 as u32;
+        for grant in params.grants.iter() {
+            granted += grant.tickets as u32;
+        }
+        if raffle.free_tickets_granted + granted > raffle.max_free_tickets { return Err(ErrorCode::TooManyFreeTickets.into()); }
+
+        for grant in params.grants {
+            credit_free_tickets(raffle, grant.holder, grant.tickets)?;
+
+            emit!(TicketsGranted {
+                raffle: raffle.key(),
+                holder: grant.holder,
+                tickets: grant.tickets,
+                authority: authority,
+                free_tickets_granted: raffle.free_tickets_granted,
+            });
         }
 
-        // Simple Checks
-        if reward_index == raffle.rewards.len() { return Err(ErrorCode::NoMoreRewards.into()); }
-        if Clock::get()?.unix_timestamp as u64 <= raffle.end_date { return Err(ErrorCode::RaffleNotEnded.into()); }
-        if raffle.end_date > bot.last_timestamp { return Err(ErrorCode::StaleRNG.into()); }
+        Ok(())
+    }
 
-        let winner_index = get_winner_index(
-            bot.rng_uses_left,
-            &bot.rng_buffer,
-            &raffle.holders,
+
+    // ------------ REDEEM VOUCHER --------------------------------
+    pub fn redeem_voucher(
+        ctx: Context<RedeemVoucher>,
+        params: RedeemVoucherParams
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let master_raffle = &ctx.accounts.master_raffle;
+        let voucher_bitmap = &mut ctx.accounts.voucher_bitmap;
+        let holder = ctx.accounts.holder.key();
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Simple Checks
+        if master_raffle.voucher_authority == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::NoVoucherAuthority.into()); }
+        if raffle.start_date == 0 || current_time < raffle.start_date { return Err(ErrorCode::RaffleNotStarted.into()); }
+        if current_time > raffle.end_date { return Err(ErrorCode::RaffleEnded.into()); }
+        if raffle.paused_at != 0 || master_raffle.paused { return Err(ErrorCode::RafflePaused.into()); }
+
+        // Each nonce can only be used once per raffle
+        let bit = (params.nonce % VOUCHERS_PER_BITMAP) as usize;
+        if voucher_bitmap.used[bit / 8] & (1 << (bit % 8)) != 0 { return Err(ErrorCode::VoucherUsed.into()); }
+
+        // The voucher is verified by the ed25519 program in the instruction right before this one
+        let instructions = &ctx.accounts.instructions;
+        let current_index = solana_program::sysvar::instructions::load_current_index_checked(instructions)?;
+        if current_index < 1 { return Err(ErrorCode::BadVoucher.into()); }
+
+        let ed25519_ix = solana_program::sysvar::instructions::load_instruction_at_checked((current_index - 1) as usize, instructions)?;
+        if ed25519_ix.program_id != solana_program::ed25519_program::id() { return Err(ErrorCode::BadVoucher.into()); }
+
+        let (signer, message) = get_ed25519_signer_and_message(&ed25519_ix.data).ok_or(ErrorCode::BadVoucher)?;
+        if signer != master_raffle.voucher_authority { return Err(ErrorCode::BadVoucher.into()); }
+        if message != get_voucher_message(&raffle.key(), &holder, params.tickets, params.nonce) { return Err(ErrorCode::BadVoucher.into()); }
+
+        credit_free_tickets(raffle, holder, params.tickets)?;
+
+        voucher_bitmap.raffle = raffle.key();
+        voucher_bitmap.used[bit / 8] |= 1 << (bit % 8);
+
+        emit!(VoucherRedeemed {
+            raffle: raffle.key(),
+            holder: holder,
+            tickets: params.tickets,
+            nonce: params.nonce,
+            free_tickets_granted: raffle.free_tickets_granted,
+        });
+
+        Ok(())
+    }
+
+    // ------------ PICK WINNER -------------------------------
+    pub fn pick_winner(
+        ctx: Context<PickWinner>,
+        params: PickWinnerParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let bot = &mut ctx.accounts.rng_bot.load_mut()?;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        let mut reward_index = get_next_reward_index(&raffle.rewards);
+        let null_winner = Pubkey::new_from_array(NULL_KEY_ARRAY).clone();
+
+        // The RNG is public once it lands, so only the owner gets to choose which reward is drawn
+        if let Some(index) = params.reward_index {
+            if !ctx.accounts.owner.is_signer { return Err(ErrorCode::OwnerMustPick.into()); }
+            if index as usize >= raffle.rewards.len() || raffle.rewards[index as usize].winner != null_winner { return Err(ErrorCode::BadRewardIndex.into()); }
+
+            // Only within the tier being drawn, a lower tier can not be skipped
+            if raffle.rewards[index as usize].tier != raffle.rewards[reward_index].tier { return Err(ErrorCode::TierNotDrawn.into()); }
+            reward_index = index as usize;
+        }
+
+        // Simple Checks
+        if reward_index == raffle.rewards.len() { return Err(ErrorCode::NoMoreRewards.into()); }
+        if current_time <= raffle.end_date { return Err(ErrorCode::RaffleNotEnded.into()); }
+        if raffle.paused_at != 0 { return Err(ErrorCode::RafflePaused.into()); }
+        if raffle.end_date > bot.last_timestamp { return Err(ErrorCode::StaleRNG.into()); }
+
+        let winner_index = get_winner_index(
+            bot.rng_uses_left,
+            &bot.rng_buffer,
+            &raffle.holders,
         );
 
         let mut winner = raffle.owner;
 
         if winner_index == !0 {
             return Err(ErrorCode::NoMoreRNG.into());
-        } else if winner_index != raffle.holders.len() {
+        }
+
+        // Jackpots are only won with the master's odds, otherwise they carry over to the next raffle
+        if raffle.rewards[reward_index].kind == RewardKind::Jackpot {
+            let master_raffle = &mut ctx.accounts.master_raffle;
+            let roll = get_jackpot_roll(&bot.rng_buffer, &raffle.key());
+            let won = roll < master_raffle.jackpot_odds_bps
+                && master_raffle.jackpot_balance > 0
+                && winner_index != raffle.holders.len();
+
+            emit!(JackpotDrawn {
+                master_raffle: master_raffle.key(),
+                raffle: raffle.key(),
+                roll: roll,
+                won: won,
+                jackpot_balance: master_raffle.jackpot_balance,
+            });
+
+            if !won {
+                raffle.rewards[reward_index].winner = raffle.owner;
+                raffle.rewards[reward_index].picked_at = current_time;
+                raffle.rewards[reward_index].reward_redeemed = true;
+                return Ok(());
+            }
+
+            // Set aside for the winner, in whatever mint the master pools now
+            raffle.rewards[reward_index].reward_mint = master_raffle.jackpot_mint;
+            raffle.rewards[reward_index].reward_amount = master_raffle.jackpot_balance;
+            master_raffle.jackpot_balance = 0;
+        }
+
+        if winner_index != raffle.holders.len() {
+
+            let ticket_roll = get_winner_ticket_roll(bot.rng_uses_left, &bot.rng_buffer, &raffle.holders);
+            let payment_options = raffle.payment_options.clone();
 
             bot.rng_uses_left -= 1;
-            raffle.holders[winner_index].tickets -= 1;
+            remove_winning_ticket(&mut raffle.holders[winner_index], ticket_roll, &payment_options);
             winner = raffle.holders[winner_index].holder;
 
         }
@@ -422,88 +1048,611 @@ pub mod raffle {
             for i in 0..raffle.rewards.len() {
                 if reward_group_id == raffle.rewards[i].group_id {
                     raffle.rewards[i].winner = winner;
+                    raffle.rewards[i].picked_at = current_time;
                 }
             }
         } else {
             raffle.rewards[reward_index].winner = winner;
+            raffle.rewards[reward_index].picked_at = current_time;
         }
 
+        emit!(WinnerPicked {
+            raffle: raffle.key(),
+            reward_index: reward_index as u8,
+            tier: raffle.rewards[reward_index].tier,
+            winner: winner,
+            claim_deadline: get_claim_deadline(&raffle, reward_index),
+        });
+
         Ok(())
     }
 
-    // ------------ CASH OUT OWNER -------------------------------
-    pub fn cash_out_owner(
-        ctx: Context<CashOutOwner>,
+    // ------------ REROLL REWARD -------------------------------
+    pub fn reroll_reward(
+        ctx: Context<RerollReward>,
+        params: RerollRewardParams,
     ) -> ProgramResult {
 
         let raffle = &mut ctx.accounts.raffle;
+        let bot = &mut ctx.accounts.rng_bot.load_mut()?;
+        let current_time = Clock::get()?.unix_timestamp as u64;
 
-        if Clock::get()?.unix_timestamp as u64 <= raffle.end_date { return Err(ErrorCode::RaffleNotEnded.into()); }
-        if ctx.accounts.payment_vault.amount == 0 { return Err(ErrorCode::NoMoreSPL.into()); }
+        // Like pick winner, only the owner gets to choose which reward is drawn again
+        let index = match params.reward_index {
+            Some(index) => {
+                if !ctx.accounts.owner.is_signer { return Err(ErrorCode::OwnerMustPick.into()); }
+                index as usize
+            },
+            None => get_rerollable_reward_index(&raffle, current_time),
+        };
 
-        // Tally up the amount to TX
-        let mut amount = 0 as u64;
-        for i in 0..raffle.payment_options.len() {
-            if raffle.payment_options[i].payment_mint == ctx.accounts.payment_vault.mint {
-                amount += raffle.payment_options[i].payment_tally * raffle.payment_options[i].payment_amount;
+        // Simple Checks
+        if index >= raffle.rewards.len() { return Err(ErrorCode::BadRewardIndex.into()); }
+        if raffle.paused_at != 0 { return Err(ErrorCode::RafflePaused.into()); }
+        if raffle.rewards[index].kind == RewardKind::Jackpot { return Err(ErrorCode::CannotReroll.into()); }
+        if raffle.rewards[index].winner == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::CannotReroll.into()); }
+        if raffle.rewards[index].winner == raffle.owner { return Err(ErrorCode::CannotReroll.into()); }
+        if raffle.rewards[index].reward_redeemed { return Err(ErrorCode::CannotReroll.into()); }
+        if raffle.rewards[index].rerolls >= raffle.max_rerolls { return Err(ErrorCode::TooManyRerolls.into()); }
+        if current_time <= raffle.rewards[index].picked_at + raffle.reroll_window { return Err(ErrorCode::RerollWindowOpen.into()); }
+        if bot.last_timestamp <= raffle.rewards[index].picked_at { return Err(ErrorCode::StaleRNG.into()); }
+
+        // The winner that did not claim is left out of the new draw
+        let old_winner = raffle.rewards[index].winner;
+        let mut holders = raffle.holders.clone();
+        for i in 0..holders.len() {
+            if holders[i].holder == old_winner {
+                holders[i].tickets = 0;
+                holders[i].entries = 0;
+            }
+        }
+
+        let winner_index = get_winner_index(
+            bot.rng_uses_left,
+            &bot.rng_buffer,
+            &holders,
+        );
+
+        if winner_index == !0 {
+            return Err(ErrorCode::NoMoreRNG.into());
+        }
+
+        // No tickets left, the owner gets it back like in pick winner
+        let mut winner = raffle.owner;
+        if winner_index != holders.len() {
+
+            // Only the old winner is left out, so the roll lands on the same ticket in the real holder
+            let ticket_roll = get_winner_ticket_roll(bot.rng_uses_left, &bot.rng_buffer, &holders);
+            let payment_options = raffle.payment_options.clone();
+
+            bot.rng_uses_left -= 1;
+            remove_winning_ticket(&mut raffle.holders[winner_index], ticket_roll, &payment_options);
+            winner = raffle.holders[winner_index].holder;
+
+        }
+
+        // Every unclaimed part of the same group moves with it
+        let reward_group_id = raffle.rewards[index].group_id.clone();
+        for i in 0..raffle.rewards.len() {
+            let in_group = i == index || (reward_group_id != Pubkey::new_from_array(NULL_KEY_ARRAY).clone() && reward_group_id == raffle.rewards[i].group_id);
+            if in_group && raffle.rewards[i].winner == old_winner && !raffle.rewards[i].reward_redeemed {
+                raffle.rewards[i].winner = winner;
+                raffle.rewards[i].picked_at = current_time;
+                raffle.rewards[i].rerolls += 1;
             }
         }
 
+        emit!(RewardRerolled {
+            raffle: raffle.key(),
+            reward_index: index as u8,
+            old_winner: old_winner,
+            new_winner: winner,
+            rerolls: raffle.rewards[index].rerolls,
+            claim_deadline: get_claim_deadline(&raffle, index),
+        });
+
+        Ok(())
+    }
+
+    // ------------ DISTRIBUTE PROCEEDS -------------------------------
+    pub fn distribute_proceeds<'info>(
+        ctx: Context<'_, '_, '_, 'info, DistributeProceeds<'info>>,
+        params: DistributeProceedsParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let payment_mint = ctx.accounts.payment_mint.key();
+        let token_program = ctx.accounts.token_program.key();
+
+        if Clock::get()?.unix_timestamp as u64 <= raffle.end_date { return Err(ErrorCode::RaffleNotEnded.into()); }
+        if raffle.paused_at != 0 { return Err(ErrorCode::RafflePaused.into()); }
+
+        // What the payees are owed is worked out from the tallies, anything else sitting in the vault is left for sweep_vault
+        let collected = get_collected_amount(&raffle.payment_options, &payment_mint);
+        let owed = get_owed_amount(&raffle, &payment_mint);
+        let mut withdrawn = get_withdrawn_amount(&raffle.withdrawals, &payment_mint);
+
+        // No payees pays everything to the owner
+        let mut payees = raffle.payees.clone();
+        let mut dust_recipient = raffle.dust_recipient;
+        if payees.len() == 0 {
+            payees.push(Payee{ recipient: raffle.owner, share_bps: MAX_BPS });
+            dust_recipient = raffle.owner;
+        }
+
+        // Remaining accounts are every payee's vault in order, then the jackpot vault
+        if ctx.remaining_accounts.len() < payees.len() { return Err(ErrorCode::BadPayeeVault.into()); }
+        for i in 0..payees.len() {
+            if get_vault_address(&payees[i].recipient, &payment_mint, &token_program) != ctx.remaining_accounts[i].key() { return Err(ErrorCode::BadPayeeVault.into()); }
+        }
+
         // TX Output
         let seeds = &[
             raffle.to_account_info().key.as_ref(),
             &[raffle.nonce],
         ];
         let signer = &[&seeds[..]];
-        let cpi_program = ctx.accounts.token_program.clone();
 
-        let output_tx = Transfer {
-            from: ctx.accounts.payment_vault.to_account_info().clone(),
-            to: ctx.accounts.owner_vault.to_account_info().clone(),
-            authority: ctx.accounts.gatekeeper.clone(),
-        };
-        let output_cpi = CpiContext::new_with_signer(cpi_program.clone(), output_tx, signer);
-        let output_tx_result = transfer(output_cpi, amount as u64);
+        // Feed the master's jackpot on the first withdrawal
+        let master_raffle = &mut ctx.accounts.master_raffle;
+        let mut jackpot_amount = 0;
+        if withdrawn == 0 && raffle.jackpot_bps > 0 && master_raffle.jackpot_mint == payment_mint {
+            let jackpot_vault = ctx.remaining_accounts.get(payees.len()).ok_or(ErrorCode::BadJackpotVault)?;
+            if get_vault_address(&master_raffle.jackpot_keeper, &master_raffle.jackpot_mint, &token_program) != jackpot_vault.key() {
+                return Err(ErrorCode::BadJackpotVault.into());
+            }
 
-        if !output_tx_result.is_ok() {
-            return Err(ErrorCode::CouldNotTX.into());
+            jackpot_amount = std::cmp::min(get_pot_share_amount(collected, raffle.jackpot_bps), owed);
+
+            // The jackpot only holds what arrived after any transfer fee
+            let jackpot_before = get_token_account(jackpot_vault)?.amount;
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.payment_vault,
+                &ctx.accounts.payment_mint,
+                jackpot_vault,
+                &ctx.accounts.gatekeeper,
+                jackpot_amount,
+                signer,
+            )?;
+
+            master_raffle.jackpot_balance += get_received_amount(jackpot_vault, jackpot_before)?;
+            withdrawn += jackpot_amount;
         }
 
-        // Mark as cashed out
-        for i in 0..raffle.payment_options.len() {
-            if raffle.payment_options[i].payment_mint == ctx.accounts.payment_vault.mint {
-                raffle.payment_options[i].cashed_out = true;
-            }
+        // Everything outstanding by default, or part of it
+        let outstanding = owed - withdrawn;
+        let amount = params.amount.unwrap_or(outstanding);
+
+        if amount > outstanding { return Err(ErrorCode::NoMoreSPL.into()); }
+        if amount == 0 && jackpot_amount == 0 { return Err(ErrorCode::NoMoreSPL.into()); }
+
+        let payee_amounts = get_payee_amounts(&payees, &dust_recipient, amount);
+        for i in 0..payees.len() {
+            if payee_amounts[i] == 0 { continue; }
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.payment_vault,
+                &ctx.accounts.payment_mint,
+                &ctx.remaining_accounts[i],
+                &ctx.accounts.gatekeeper,
+                payee_amounts[i],
+                signer,
+            )?;
         }
 
+        withdrawn += amount;
+        record_withdrawal(raffle, &payment_mint, withdrawn, owed);
+
+        emit!(ProceedsDistributed {
+            raffle: raffle.key(),
+            payment_mint: payment_mint,
+            amount: amount,
+            payee_amounts: payee_amounts,
+            jackpot_amount: jackpot_amount,
+            collected: collected,
+            withdrawn: withdrawn,
+            outstanding: owed - withdrawn,
+        });
+
+        Ok(())
+    }
+
+    // ------------ SWEEP VAULT -------------------------------
+    pub fn sweep_vault(
+        ctx: Context<SweepVault>,
+    ) -> ProgramResult {
+
+        let raffle = &ctx.accounts.raffle;
+        let mint = ctx.accounts.mint.key();
+
+        // Only what the raffle does not owe anyone, donations and stray transfers
+        let reserved = get_reserved_amount(raffle, &mint);
+        let amount = get_token_account(&ctx.accounts.vault)?.amount.saturating_sub(reserved);
+
+        if amount == 0 { return Err(ErrorCode::NoMoreSPL.into()); }
+
+        // TX Output
+        let seeds = &[
+            raffle.to_account_info().key.as_ref(),
+            &[raffle.nonce],
+        ];
+        let signer = &[&seeds[..]];
+
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.owner_vault,
+            &ctx.accounts.gatekeeper,
+            amount,
+            signer,
+        )?;
+
+        emit!(VaultSwept {
+            raffle: raffle.key(),
+            mint: mint,
+            amount: amount,
+            reserved: reserved,
+        });
+
         Ok(())
     }
 
     // ------------ REDEEM REWARD -------------------------------
     pub fn redeem_reward(
         ctx: Context<RedeemReward>,
+        params: RedeemRewardParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let winner = ctx.accounts.winner.key();
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let kinds = [RewardKind::Spl, RewardKind::PotShare];
+
+        // Without an index, the first reward the winner can redeem is used
+        let index = match params.reward_index {
+            Some(index) => index as usize,
+            None => get_redeemable_reward_index(&raffle.rewards, &winner, &kinds),
+        };
+
+        // Simple Checks
+        if index >= raffle.rewards.len() { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if raffle.rewards[index].winner != winner || raffle.rewards[index].reward_redeemed { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if !kinds.contains(&raffle.rewards[index].kind) { return Err(ErrorCode::BadRewardIndex.into()); }
+        if current_time <= raffle.end_date { return Err(ErrorCode::RaffleNotEnded.into()); }
+        if raffle.paused_at != 0 { return Err(ErrorCode::RafflePaused.into()); }
+        if winner == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::BadWinner.into()); }
+        if raffle.rewards[index].reward_mint != ctx.accounts.reward_mint.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+
+        raffle.rewards[index].reward_amount = get_reward_amount(&raffle, index);
+
+        // TX Output
+        let seeds = &[
+            raffle.to_account_info().key.as_ref(),
+            &[raffle.nonce],
+        ];
+        let signer = &[&seeds[..]];
+
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.reward_vault,
+            &ctx.accounts.reward_mint,
+            &ctx.accounts.winner_vault,
+            &ctx.accounts.gatekeeper,
+            raffle.rewards[index].reward_amount as u64,
+            signer,
+        )?;
+
+        raffle.rewards[index].reward_redeemed = true;
+
+        Ok(())
+    }
+
+    // ------------ REDEEM REWARDS -------------------------------
+    pub fn redeem_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, RedeemRewards<'info>>,
     ) -> ProgramResult {
 
         let raffle = &mut ctx.accounts.raffle;
         let winner = ctx.accounts.winner.key();
         let current_time = Clock::get()?.unix_timestamp as u64;
-        let past_redeem_threshold = current_time > raffle.end_date + NOT_REDEEMED_LENGTH && raffle.owner == winner;
-        let mut index = raffle.rewards.len();
+        let kinds = [RewardKind::Spl, RewardKind::PotShare];
+
+        // Simple Checks
+        if current_time <= raffle.end_date { return Err(ErrorCode::RaffleNotEnded.into()); }
+        if raffle.paused_at != 0 { return Err(ErrorCode::RafflePaused.into()); }
+        if winner == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::BadWinner.into()); }
+
+        // TX Output
+        let seeds = &[
+            raffle.to_account_info().key.as_ref(),
+            &[raffle.nonce],
+        ];
+        let signer = &[&seeds[..]];
+        let token_program = ctx.accounts.token_program.key();
 
+        // Remaining accounts are [reward mint, reward vault, winner vault] triples, one for every reward the winner can redeem in order
+        let mut triple_index = 0;
         for i in 0..raffle.rewards.len() {
-            if raffle.rewards[i].winner == winner || past_redeem_threshold {
-                if !raffle.rewards[i].reward_redeemed {
-                    index = i;
-                    break;
+            if raffle.rewards[i].winner != winner || raffle.rewards[i].reward_redeemed { continue; }
+            if !kinds.contains(&raffle.rewards[i].kind) { continue; }
+            if ctx.remaining_accounts.len() < (triple_index + 1) * 3 { return Err(ErrorCode::BadWinnerVault.into()); }
+
+            let reward_mint = &ctx.remaining_accounts[triple_index * 3];
+            let reward_vault = &ctx.remaining_accounts[triple_index * 3 + 1];
+            let winner_vault = &ctx.remaining_accounts[triple_index * 3 + 2];
+            triple_index += 1;
+
+            if raffle.rewards[i].reward_mint != reward_mint.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+            if get_vault_address(&ctx.accounts.gatekeeper.key(), reward_mint.key, &token_program) != reward_vault.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+            if get_vault_address(&winner, reward_mint.key, &token_program) != winner_vault.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+
+            raffle.rewards[i].reward_amount = get_reward_amount(&raffle, i);
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                reward_vault,
+                reward_mint,
+                winner_vault,
+                &ctx.accounts.gatekeeper,
+                raffle.rewards[i].reward_amount as u64,
+                signer,
+            )?;
+
+            raffle.rewards[i].reward_redeemed = true;
+        }
+
+        if triple_index == 0 { return Err(ErrorCode::NoWinnerLeft.into()); }
+
+        Ok(())
+    }
+
+    // ------------ RECLAIM EXPIRED REWARD -------------------------------
+    pub fn reclaim_expired_reward(
+        ctx: Context<ReclaimExpiredReward>,
+        params: ReclaimExpiredRewardParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let index = params.reward_index as usize;
+
+        // Simple Checks
+        if index >= raffle.rewards.len() { return Err(ErrorCode::BadRewardIndex.into()); }
+        if raffle.rewards[index].winner == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::BadWinner.into()); }
+        if raffle.rewards[index].reward_redeemed { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if current_time <= get_claim_deadline(&raffle, index) { return Err(ErrorCode::ClaimWindowOpen.into()); }
+
+        raffle.rewards[index].reward_amount = get_reward_amount(&raffle, index);
+
+        // TX Output
+        let seeds = &[
+            raffle.to_account_info().key.as_ref(),
+            &[raffle.nonce],
+        ];
+        let signer = &[&seeds[..]];
+
+        match raffle.rewards[index].kind {
+            RewardKind::Spl | RewardKind::PotShare => {
+                let reward_mint = raffle.rewards[index].reward_mint;
+                let token_program = ctx.accounts.token_program.key();
+                if reward_mint != ctx.accounts.reward_mint.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+                if get_vault_address(&ctx.accounts.gatekeeper.key(), &reward_mint, &token_program) != ctx.accounts.reward_vault.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+                if get_vault_address(&ctx.accounts.owner.key(), &reward_mint, &token_program) != ctx.accounts.owner_vault.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+
+                transfer_tokens(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.reward_vault,
+                    &ctx.accounts.reward_mint,
+                    &ctx.accounts.owner_vault,
+                    &ctx.accounts.gatekeeper,
+                    raffle.rewards[index].reward_amount as u64,
+                    signer,
+                )?;
+            },
+            RewardKind::Lamports => {
+                let output_tx_result = solana_program::program::invoke_signed(
+                    &solana_program::system_instruction::transfer(
+                        ctx.accounts.gatekeeper.key,
+                        ctx.accounts.owner.key,
+                        raffle.rewards[index].reward_amount,
+                    ),
+                    &[
+                        ctx.accounts.gatekeeper.clone(),
+                        ctx.accounts.owner.to_account_info().clone(),
+                        ctx.accounts.system_program.clone(),
+                    ],
+                    signer,
+                );
+
+                if !output_tx_result.is_ok() {
+                    return Err(ErrorCode::CouldNotTX.into());
+                }
+            },
+            // Programmable NFTs can only be moved by Token Metadata, see reclaim_expired_pnft_reward
+            RewardKind::ProgrammableNft => { return Err(ErrorCode::BadRewardIndex.into()); },
+            // An unclaimed jackpot goes back into the pool instead of to the owner, the tokens never left the jackpot vault
+            RewardKind::Jackpot => {
+                let master_raffle = &mut ctx.accounts.master_raffle;
+                if raffle.rewards[index].reward_mint == master_raffle.jackpot_mint {
+                    master_raffle.jackpot_balance += raffle.rewards[index].reward_amount;
+                } else {
+                    // The master pools another mint now, so these tokens can't be counted in its balance
+                    let reward_mint = raffle.rewards[index].reward_mint;
+                    let token_program = ctx.accounts.token_program.key();
+                    if master_raffle.jackpot_keeper != ctx.accounts.jackpot_keeper.key() { return Err(ErrorCode::BadJackpotVault.into()); }
+                    if reward_mint != ctx.accounts.reward_mint.key() { return Err(ErrorCode::BadJackpotVault.into()); }
+                    if get_vault_address(&master_raffle.jackpot_keeper, &reward_mint, &token_program) != ctx.accounts.reward_vault.key() { return Err(ErrorCode::BadJackpotVault.into()); }
+                    if get_vault_address(&ctx.accounts.owner.key(), &reward_mint, &token_program) != ctx.accounts.owner_vault.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+
+                    let master_raffle_key = master_raffle.key();
+                    let jackpot_seeds = &[
+                        JACKPOT_SEED,
+                        master_raffle_key.as_ref(),
+                        &[master_raffle.jackpot_nonce],
+                    ];
+                    let jackpot_signer = &[&jackpot_seeds[..]];
+
+                    transfer_tokens(
+                        &ctx.accounts.token_program,
+                        &ctx.accounts.reward_vault,
+                        &ctx.accounts.reward_mint,
+                        &ctx.accounts.owner_vault,
+                        &ctx.accounts.jackpot_keeper,
+                        raffle.rewards[index].reward_amount as u64,
+                        jackpot_signer,
+                    )?;
                 }
+            },
+        }
+
+        raffle.rewards[index].reward_redeemed = true;
+
+        emit!(RewardReclaimed {
+            raffle: raffle.key(),
+            reward_index: params.reward_index,
+            forfeited_by: raffle.rewards[index].winner,
+            reward_mint: raffle.rewards[index].reward_mint,
+            reward_amount: raffle.rewards[index].reward_amount,
+        });
+
+        Ok(())
+    }
+
+    // ------------ FUND CRANK FEE -------------------------------
+    pub fn fund_crank_fee(
+        ctx: Context<FundCrankFee>,
+        params: FundCrankFeeParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let gatekeeper = &ctx.accounts.gatekeeper;
+
+        // The gatekeeper holds the fees, keep it rent exempt so payouts never leave it short
+        let rent_reserve = Rent::get()?.minimum_balance(0);
+        let amount = params.amount + rent_reserve.saturating_sub(gatekeeper.lamports());
+
+        if amount > ctx.accounts.owner.lamports() { return Err(ErrorCode::BadOwnerRewardBalance.into()); }
+
+        if amount > 0 {
+            let sol_tx_result = solana_program::program::invoke(
+                &solana_program::system_instruction::transfer(ctx.accounts.owner.key, gatekeeper.key, amount),
+                &[
+                    ctx.accounts.owner.to_account_info().clone(),
+                    gatekeeper.clone(),
+                    ctx.accounts.system_program.clone(),
+                ],
+            );
+
+            if !sol_tx_result.is_ok() {
+                return Err(ErrorCode::CouldNotTX.into());
             }
         }
 
+        raffle.crank_fee = params.crank_fee;
+        raffle.crank_fee_balance += params.amount;
+
+        Ok(())
+    }
+
+    // ------------ RECLAIM CRANK FEE -------------------------------
+    pub fn reclaim_crank_fee(
+        ctx: Context<ReclaimCrankFee>,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+
+        if raffle.crank_fee_balance == 0 { return Err(ErrorCode::NoCrankFee.into()); }
+
+        // TX Output
+        let seeds = &[
+            raffle.to_account_info().key.as_ref(),
+            &[raffle.nonce],
+        ];
+        let signer = &[&seeds[..]];
+
+        let output_tx_result = solana_program::program::invoke_signed(
+            &solana_program::system_instruction::transfer(
+                ctx.accounts.gatekeeper.key,
+                ctx.accounts.owner.key,
+                raffle.crank_fee_balance,
+            ),
+            &[
+                ctx.accounts.gatekeeper.clone(),
+                ctx.accounts.owner.to_account_info().clone(),
+                ctx.accounts.system_program.clone(),
+            ],
+            signer,
+        );
+
+        if !output_tx_result.is_ok() {
+            return Err(ErrorCode::CouldNotTX.into());
+        }
+
+        raffle.crank_fee_balance = 0;
+
+        Ok(())
+    }
+
+    // ------------ DISTRIBUTE REWARD -------------------------------
+    pub fn distribute_reward(
+        ctx: Context<DistributeReward>,
+        params: DistributeRewardParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let winner = ctx.accounts.winner.key();
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let kinds = [RewardKind::Spl, RewardKind::PotShare];
+
+        // Without an index, the first reward the winner can redeem is used
+        let index = match params.reward_index {
+            Some(index) => index as usize,
+            None => get_redeemable_reward_index(&raffle.rewards, &winner, &kinds),
+        };
+
         // Simple Checks
-        if index == raffle.rewards.len() { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if index >= raffle.rewards.len() { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if raffle.rewards[index].winner != winner || raffle.rewards[index].reward_redeemed { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if !kinds.contains(&raffle.rewards[index].kind) { return Err(ErrorCode::BadRewardIndex.into()); }
         if current_time <= raffle.end_date { return Err(ErrorCode::RaffleNotEnded.into()); }
+        if raffle.paused_at != 0 { return Err(ErrorCode::RafflePaused.into()); }
         if winner == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::BadWinner.into()); }
-        if raffle.rewards[index].reward_mint != ctx.accounts.winner_vault.mint { return Err(ErrorCode::BadWinnerVault.into()); }
+        if raffle.rewards[index].reward_mint != ctx.accounts.reward_mint.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+        if get_vault_address(&winner, &raffle.rewards[index].reward_mint, ctx.accounts.token_program.key) != ctx.accounts.winner_vault.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+
+        // Winners don't need to show up, the caller pays for their vault if they don't have one
+        if ctx.accounts.winner_vault.data_is_empty() {
+            let vault_rent = Rent::get()?.minimum_balance(get_vault_size(&ctx.accounts.reward_mint)?);
+            if ctx.accounts.payer.lamports() < vault_rent { return Err(ErrorCode::NotEnoughForVault.into()); }
+
+            let create_result = solana_program::program::invoke(
+                &create_vault_instruction(
+                    ctx.accounts.payer.key,
+                    &winner,
+                    &raffle.rewards[index].reward_mint,
+                    ctx.accounts.token_program.key,
+                ),
+                &[
+                    ctx.accounts.payer.to_account_info().clone(),
+                    ctx.accounts.winner_vault.clone(),
+                    ctx.accounts.winner.clone(),
+                    ctx.accounts.reward_mint.clone(),
+                    ctx.accounts.system_program.clone(),
+                    ctx.accounts.token_program.clone(),
+                    ctx.accounts.rent.clone(),
+                    ctx.accounts.associated_token_program.clone(),
+                ],
+            );
+
+            if !create_result.is_ok() {
+                return Err(ErrorCode::CouldNotTX.into());
+            }
+        }
+
+        raffle.rewards[index].reward_amount = get_reward_amount(&raffle, index);
 
         // TX Output
         let seeds = &[
@@ -511,15 +1660,83 @@ pub mod raffle {
             &[raffle.nonce],
         ];
         let signer = &[&seeds[..]];
-        let cpi_program = ctx.accounts.token_program.clone();
 
-        let output_tx = Transfer {
-            from: ctx.accounts.reward_vault.to_account_info().clone(),
-            to: ctx.accounts.winner_vault.to_account_info().clone(),
-            authority: ctx.accounts.gatekeeper.clone(),
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.reward_vault,
+            &ctx.accounts.reward_mint,
+            &ctx.accounts.winner_vault,
+            &ctx.accounts.gatekeeper,
+            raffle.rewards[index].reward_amount as u64,
+            signer,
+        )?;
+
+        raffle.rewards[index].reward_redeemed = true;
+
+        let crank_fee = pay_crank_fee(
+            raffle,
+            &ctx.accounts.gatekeeper,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program,
+            signer,
+        )?;
+
+        emit!(RewardDistributed {
+            raffle: raffle.key(),
+            reward_index: index as u8,
+            winner: winner,
+            reward_amount: raffle.rewards[index].reward_amount,
+            crank_fee: crank_fee,
+        });
+
+        Ok(())
+    }
+
+    // ------------ DISTRIBUTE SOL REWARD -------------------------------
+    pub fn distribute_sol_reward(
+        ctx: Context<DistributeSolReward>,
+        params: DistributeSolRewardParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let winner = ctx.accounts.winner.key();
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let kinds = [RewardKind::Lamports];
+
+        // Without an index, the first reward the winner can redeem is used
+        let index = match params.reward_index {
+            Some(index) => index as usize,
+            None => get_redeemable_reward_index(&raffle.rewards, &winner, &kinds),
         };
-        let output_cpi = CpiContext::new_with_signer(cpi_program.clone(), output_tx, signer);
-        let output_tx_result = transfer(output_cpi, raffle.rewards[index].reward_amount as u64);
+
+        // Simple Checks
+        if index >= raffle.rewards.len() { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if raffle.rewards[index].winner != winner || raffle.rewards[index].reward_redeemed { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if !kinds.contains(&raffle.rewards[index].kind) { return Err(ErrorCode::BadRewardIndex.into()); }
+        if current_time <= raffle.end_date { return Err(ErrorCode::RaffleNotEnded.into()); }
+        if raffle.paused_at != 0 { return Err(ErrorCode::RafflePaused.into()); }
+        if winner == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::BadWinner.into()); }
+
+        // TX Output
+        let seeds = &[
+            raffle.to_account_info().key.as_ref(),
+            &[raffle.nonce],
+        ];
+        let signer = &[&seeds[..]];
+
+        let output_tx_result = solana_program::program::invoke_signed(
+            &solana_program::system_instruction::transfer(
+                ctx.accounts.gatekeeper.key,
+                &winner,
+                raffle.rewards[index].reward_amount,
+            ),
+            &[
+                ctx.accounts.gatekeeper.clone(),
+                ctx.accounts.winner.clone(),
+                ctx.accounts.system_program.clone(),
+            ],
+            signer,
+        );
 
         if !output_tx_result.is_ok() {
             return Err(ErrorCode::CouldNotTX.into());
@@ -527,159 +1744,1897 @@ pub mod raffle {
 
         raffle.rewards[index].reward_redeemed = true;
 
-        Ok(())
-    }
+        let crank_fee = pay_crank_fee(
+            raffle,
+            &ctx.accounts.gatekeeper,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program,
+            signer,
+        )?;
+
+        emit!(RewardDistributed {
+            raffle: raffle.key(),
+            reward_index: index as u8,
+            winner: winner,
+            reward_amount: raffle.rewards[index].reward_amount,
+            crank_fee: crank_fee,
+        });
+
+        Ok(())
+    }
+
+    // ------------ DISTRIBUTE JACKPOT -------------------------------
+    pub fn distribute_jackpot(
+        ctx: Context<DistributeJackpot>,
+        params: DistributeJackpotParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let master_raffle = &mut ctx.accounts.master_raffle;
+        let winner = ctx.accounts.winner.key();
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let kinds = [RewardKind::Jackpot];
+
+        // Without an index, the first reward the winner can redeem is used
+        let index = match params.reward_index {
+            Some(index) => index as usize,
+            None => get_redeemable_reward_index(&raffle.rewards, &winner, &kinds),
+        };
+
+        // Simple Checks
+        if index >= raffle.rewards.len() { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if raffle.rewards[index].winner != winner || raffle.rewards[index].reward_redeemed { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if !kinds.contains(&raffle.rewards[index].kind) { return Err(ErrorCode::BadRewardIndex.into()); }
+        if current_time <= raffle.end_date { return Err(ErrorCode::RaffleNotEnded.into()); }
+        if raffle.paused_at != 0 { return Err(ErrorCode::RafflePaused.into()); }
+        if winner == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::BadWinner.into()); }
+        if raffle.rewards[index].reward_mint != ctx.accounts.jackpot_mint.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+        if get_vault_address(&winner, &raffle.rewards[index].reward_mint, ctx.accounts.token_program.key) != ctx.accounts.winner_vault.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+
+        // A jackpot that ran out of holders goes back into the pool instead of to the owner
+        if winner == raffle.owner {
+            master_raffle.jackpot_balance += raffle.rewards[index].reward_amount;
+        } else {
+            // Winners don't need to show up, the caller pays for their vault if they don't have one
+            if ctx.accounts.winner_vault.data_is_empty() {
+                let vault_rent = Rent::get()?.minimum_balance(get_vault_size(&ctx.accounts.jackpot_mint)?);
+                if ctx.accounts.payer.lamports() < vault_rent { return Err(ErrorCode::NotEnoughForVault.into()); }
+
+                let create_result = solana_program::program::invoke(
+                    &create_vault_instruction(
+                        ctx.accounts.payer.key,
+                        &winner,
+                        &raffle.rewards[index].reward_mint,
+                        ctx.accounts.token_program.key,
+                    ),
+                    &[
+                        ctx.accounts.payer.to_account_info().clone(),
+                        ctx.accounts.winner_vault.clone(),
+                        ctx.accounts.winner.clone(),
+                        ctx.accounts.jackpot_mint.clone(),
+                        ctx.accounts.system_program.clone(),
+                        ctx.accounts.token_program.clone(),
+                        ctx.accounts.rent.clone(),
+                        ctx.accounts.associated_token_program.clone(),
+                    ],
+                );
+
+                if !create_result.is_ok() {
+                    return Err(ErrorCode::CouldNotTX.into());
+                }
+            }
+
+            // TX Output
+            let master_raffle_key = master_raffle.key();
+            let jackpot_seeds = &[
+                JACKPOT_SEED,
+                master_raffle_key.as_ref(),
+                &[master_raffle.jackpot_nonce],
+            ];
+            let jackpot_signer = &[&jackpot_seeds[..]];
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.jackpot_vault,
+                &ctx.accounts.jackpot_mint,
+                &ctx.accounts.winner_vault,
+                &ctx.accounts.jackpot_keeper,
+                raffle.rewards[index].reward_amount as u64,
+                jackpot_signer,
+            )?;
+        }
+
+        raffle.rewards[index].reward_redeemed = true;
+
+        // The crank fee comes out of the raffle's gatekeeper like the other rewards
+        let seeds = &[
+            raffle.to_account_info().key.as_ref(),
+            &[raffle.nonce],
+        ];
+        let signer = &[&seeds[..]];
+
+        let crank_fee = pay_crank_fee(
+            raffle,
+            &ctx.accounts.gatekeeper,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program,
+            signer,
+        )?;
+
+        emit!(RewardDistributed {
+            raffle: raffle.key(),
+            reward_index: index as u8,
+            winner: winner,
+            reward_amount: raffle.rewards[index].reward_amount,
+            crank_fee: crank_fee,
+        });
+
+        Ok(())
+    }
+
+    // ------------ REDEEM SOL REWARD -------------------------------
+    pub fn redeem_sol_reward(
+        ctx: Context<RedeemSolReward>,
+        params: RedeemSolRewardParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let winner = ctx.accounts.winner.key();
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let kinds = [RewardKind::Lamports];
+
+        // Without an index, the first reward the winner can redeem is used
+        let index = match params.reward_index {
+            Some(index) => index as usize,
+            None => get_redeemable_reward_index(&raffle.rewards, &winner, &kinds),
+        };
+
+        // Simple Checks
+        if index >= raffle.rewards.len() { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if raffle.rewards[index].winner != winner || raffle.rewards[index].reward_redeemed { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if !kinds.contains(&raffle.rewards[index].kind) { return Err(ErrorCode::BadRewardIndex.into()); }
+        if current_time <= raffle.end_date { return Err(ErrorCode::RaffleNotEnded.into()); }
+        if raffle.paused_at != 0 { return Err(ErrorCode::RafflePaused.into()); }
+        if winner == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::BadWinner.into()); }
+
+        // TX Output
+        let seeds = &[
+            raffle.to_account_info().key.as_ref(),
+            &[raffle.nonce],
+        ];
+        let signer = &[&seeds[..]];
+
+        let output_tx_result = solana_program::program::invoke_signed(
+            &solana_program::system_instruction::transfer(
+                ctx.accounts.gatekeeper.key,
+                &winner,
+                raffle.rewards[index].reward_amount,
+            ),
+            &[
+                ctx.accounts.gatekeeper.clone(),
+                ctx.accounts.winner.to_account_info().clone(),
+                ctx.accounts.system_program.clone(),
+            ],
+            signer,
+        );
+
+        if !output_tx_result.is_ok() {
+            return Err(ErrorCode::CouldNotTX.into());
+        }
+
+        raffle.rewards[index].reward_redeemed = true;
+
+        Ok(())
+    }
+
+    // ------------ REDEEM JACKPOT -------------------------------
+    pub fn redeem_jackpot(
+        ctx: Context<RedeemJackpot>,
+        params: RedeemJackpotParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let master_raffle = &mut ctx.accounts.master_raffle;
+        let winner = ctx.accounts.winner.key();
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let kinds = [RewardKind::Jackpot];
+
+        // Without an index, the first reward the winner can redeem is used
+        let index = match params.reward_index {
+            Some(index) => index as usize,
+            None => get_redeemable_reward_index(&raffle.rewards, &winner, &kinds),
+        };
+
+        // Simple Checks
+        if index >= raffle.rewards.len() { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if raffle.rewards[index].winner != winner || raffle.rewards[index].reward_redeemed { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if !kinds.contains(&raffle.rewards[index].kind) { return Err(ErrorCode::BadRewardIndex.into()); }
+        if current_time <= raffle.end_date { return Err(ErrorCode::RaffleNotEnded.into()); }
+        if raffle.paused_at != 0 { return Err(ErrorCode::RafflePaused.into()); }
+        if winner == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::BadWinner.into()); }
+        if raffle.rewards[index].reward_mint != ctx.accounts.jackpot_mint.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+
+        // A jackpot that ran out of holders goes back into the pool, unless the pool moved to another mint
+        if winner == raffle.owner && raffle.rewards[index].reward_mint == master_raffle.jackpot_mint {
+            master_raffle.jackpot_balance += raffle.rewards[index].reward_amount;
+            raffle.rewards[index].reward_redeemed = true;
+            return Ok(());
+        }
+
+        // TX Output
+        let master_raffle_key = master_raffle.key();
+        let seeds = &[
+            JACKPOT_SEED,
+            master_raffle_key.as_ref(),
+            &[master_raffle.jackpot_nonce],
+        ];
+        let signer = &[&seeds[..]];
+
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.jackpot_vault,
+            &ctx.accounts.jackpot_mint,
+            &ctx.accounts.winner_vault,
+            &ctx.accounts.jackpot_keeper,
+            raffle.rewards[index].reward_amount as u64,
+            signer,
+        )?;
+
+        raffle.rewards[index].reward_redeemed = true;
+
+        Ok(())
+    }
+
+    // ------------ LOAD PNFT REWARD -------------------------------
+    pub fn load_pnft_reward(
+        ctx: Context<LoadPnftReward>,
+        params: LoadRewardParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+
+        // Programmable NFTs are one of a kind, Token Metadata moves them into the gatekeeper's vault and keeps it frozen
+        if params.name.len() > MAX_NAME_LENGTH { return Err(ErrorCode::NameTooLong.into()); }
+        if raffle.start_date != 0  { return Err(ErrorCode::RaffleStarted.into()); }
+        if params.reward_amount != 1 { return Err(ErrorCode::BadPnftAmount.into()); }
+        if get_token_account(&ctx.accounts.owner_vault)?.amount < 1 { return Err(ErrorCode::BadOwnerRewardBalance.into()); }
+        if raffle.reward_count < (raffle.rewards.len() + 1) as u8 { return Err(ErrorCode::TooManyRewards.into()); }
+
+        transfer_pnft(
+            &ctx.accounts.pnft,
+            &ctx.accounts.owner_vault,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.reward_vault,
+            &ctx.accounts.gatekeeper,
+            &ctx.accounts.reward_mint,
+            &ctx.accounts.owner.to_account_info(),
+            &[],
+        )?;
+
+        raffle.rewards.push(
+            Reward{
+                name: String::from(params.name),
+                group_id: params.group_id.clone(),
+                reward_amount: 1,
+                reward_mint: ctx.accounts.reward_mint.key(),
+                winner: Pubkey::new_from_array(NULL_KEY_ARRAY).clone(),
+                reward_redeemed: false,
+                sponsor: ctx.accounts.owner.key(),
+                kind: RewardKind::ProgrammableNft,
+                pot_share_bps: 0,
+                picked_at: 0,
+                rerolls: 0,
+                tier: params.tier,
+            }
+        );
+
+        Ok(())
+    }
+
+    // ------------ REDEEM PNFT REWARD -------------------------------
+    pub fn redeem_pnft_reward(
+        ctx: Context<RedeemPnftReward>,
+        params: RedeemPnftRewardParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let winner = ctx.accounts.winner.key();
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let kinds = [RewardKind::ProgrammableNft];
+
+        // Without an index, the first reward the winner can redeem is used
+        let index = match params.reward_index {
+            Some(index) => index as usize,
+            None => get_redeemable_reward_index(&raffle.rewards, &winner, &kinds),
+        };
+
+        // Simple Checks
+        if index >= raffle.rewards.len() { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if raffle.rewards[index].winner != winner || raffle.rewards[index].reward_redeemed { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if !kinds.contains(&raffle.rewards[index].kind) { return Err(ErrorCode::BadRewardIndex.into()); }
+        if current_time <= raffle.end_date { return Err(ErrorCode::RaffleNotEnded.into()); }
+        if raffle.paused_at != 0 { return Err(ErrorCode::RafflePaused.into()); }
+        if winner == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::BadWinner.into()); }
+        if raffle.rewards[index].reward_mint != ctx.accounts.reward_mint.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+
+        // TX Output
+        let seeds = &[
+            raffle.to_account_info().key.as_ref(),
+            &[raffle.nonce],
+        ];
+        let signer = &[&seeds[..]];
+
+        transfer_pnft(
+            &ctx.accounts.pnft,
+            &ctx.accounts.reward_vault,
+            &ctx.accounts.gatekeeper,
+            &ctx.accounts.winner_vault,
+            &ctx.accounts.winner.to_account_info(),
+            &ctx.accounts.reward_mint,
+            &ctx.accounts.winner.to_account_info(),
+            signer,
+        )?;
+
+        raffle.rewards[index].reward_redeemed = true;
+
+        Ok(())
+    }
+
+    // ------------ DISTRIBUTE PNFT REWARD -------------------------------
+    pub fn distribute_pnft_reward(
+        ctx: Context<DistributePnftReward>,
+        params: DistributePnftRewardParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let winner = ctx.accounts.winner.key();
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let kinds = [RewardKind::ProgrammableNft];
+
+        // Without an index, the first reward the winner can redeem is used
+        let index = match params.reward_index {
+            Some(index) => index as usize,
+            None => get_redeemable_reward_index(&raffle.rewards, &winner, &kinds),
+        };
+
+        // Simple Checks
+        if index >= raffle.rewards.len() { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if raffle.rewards[index].winner != winner || raffle.rewards[index].reward_redeemed { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if !kinds.contains(&raffle.rewards[index].kind) { return Err(ErrorCode::BadRewardIndex.into()); }
+        if current_time <= raffle.end_date { return Err(ErrorCode::RaffleNotEnded.into()); }
+        if raffle.paused_at != 0 { return Err(ErrorCode::RafflePaused.into()); }
+        if winner == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::BadWinner.into()); }
+        if raffle.rewards[index].reward_mint != ctx.accounts.reward_mint.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+
+        // TX Output, Token Metadata creates the winner's vault and token record with the caller's SOL
+        let seeds = &[
+            raffle.to_account_info().key.as_ref(),
+            &[raffle.nonce],
+        ];
+        let signer = &[&seeds[..]];
+
+        transfer_pnft(
+            &ctx.accounts.pnft,
+            &ctx.accounts.reward_vault,
+            &ctx.accounts.gatekeeper,
+            &ctx.accounts.winner_vault,
+            &ctx.accounts.winner,
+            &ctx.accounts.reward_mint,
+            &ctx.accounts.payer.to_account_info(),
+            signer,
+        )?;
+
+        raffle.rewards[index].reward_redeemed = true;
+
+        let crank_fee = pay_crank_fee(
+            raffle,
+            &ctx.accounts.gatekeeper,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.pnft.system_program,
+            signer,
+        )?;
+
+        emit!(RewardDistributed {
+            raffle: raffle.key(),
+            reward_index: index as u8,
+            winner: winner,
+            reward_amount: raffle.rewards[index].reward_amount,
+            crank_fee: crank_fee,
+        });
+
+        Ok(())
+    }
+
+    // ------------ RECLAIM EXPIRED PNFT REWARD -------------------------------
+    pub fn reclaim_expired_pnft_reward(
+        ctx: Context<ReclaimExpiredPnftReward>,
+        params: ReclaimExpiredPnftRewardParams,
+    ) -> ProgramResult {
+
+        let raffle = &mut ctx.accounts.raffle;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let index = params.reward_index as usize;
+
+        // Simple Checks
+        if index >= raffle.rewards.len() { return Err(ErrorCode::BadRewardIndex.into()); }
+        if raffle.rewards[index].kind != RewardKind::ProgrammableNft { return Err(ErrorCode::BadRewardIndex.into()); }
+        if raffle.rewards[index].winner == Pubkey::new_from_array(NULL_KEY_ARRAY).clone() { return Err(ErrorCode::BadWinner.into()); }
+        if raffle.rewards[index].reward_redeemed { return Err(ErrorCode::NoWinnerLeft.into()); }
+        if current_time <= get_claim_deadline(&raffle, index) { return Err(ErrorCode::ClaimWindowOpen.into()); }
+        if raffle.rewards[index].reward_mint != ctx.accounts.reward_mint.key() { return Err(ErrorCode::BadWinnerVault.into()); }
+
+        // TX Output
+        let seeds = &[
+            raffle.to_account_info().key.as_ref(),
+            &[raffle.nonce],
+        ];
+        let signer = &[&seeds[..]];
+
+        transfer_pnft(
+            &ctx.accounts.pnft,
+            &ctx.accounts.reward_vault,
+            &ctx.accounts.gatekeeper,
+            &ctx.accounts.owner_vault,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.reward_mint,
+            &ctx.accounts.owner.to_account_info(),
+            signer,
+        )?;
+
+        raffle.rewards[index].reward_redeemed = true;
+
+        emit!(RewardReclaimed {
+            raffle: raffle.key(),
+            reward_index: params.reward_index,
+            forfeited_by: raffle.rewards[index].winner,
+            reward_mint: raffle.rewards[index].reward_mint,
+            reward_amount: raffle.rewards[index].reward_amount,
+        });
+
+        Ok(())
+    }
+
+    // ------------ CREATE TEMPLATE -------------------------------
+    pub fn create_template(
+        ctx: Context<CreateTemplate>,
+        params: CreateTemplateParams,
+    ) -> ProgramResult {
+
+        let template = &mut ctx.accounts.template;
+
+        // Simple Checks
+        if params.name.len() > MAX_NAME_LENGTH { return Err(ErrorCode::NameTooLong.into()); }
+        if params.payment_option_count < 1 { return Err(ErrorCode::NeedPaymentOption.into()); }
+        if params.reward_count < 1 { return Err(ErrorCode::NeedReward.into()); }
+        if params.max_holder_count < 1 { return Err(ErrorCode::NeedHolders.into()); }
+        if params.duration < 1 || params.duration > MAX_RAFFLE_LENGTH { return Err(ErrorCode::BadTemplateDuration.into()); }
+        if params.claim_window < MIN_CLAIM_WINDOW || params.claim_window > MAX_CLAIM_WINDOW { return Err(ErrorCode::BadClaimWindow.into()); }
+
+        // Check Keeper
+        let (keeper, nonce) = Pubkey::find_program_address(
+            &[template.to_account_info().key.as_ref()],
+            ctx.program_id,
+        );
+
+        if &keeper != ctx.accounts.keeper.to_account_info().key {
+            return Err(ErrorCode::BadTemplateKeeper.into());
+        }
+
+        // Authorities
+        template.name = String::from(params.name);
+        template.template = template.key();
+        template.master_raffle = ctx.accounts.master_raffle.key();
+        template.owner = ctx.accounts.owner.key();
+        template.keeper = keeper;
+        template.nonce = nonce;
+        template.index = params.index;
+
+        // Limits
+        template.max_tickets_per_holder = params.max_tickets_per_holder;
+        template.payment_option_count = params.payment_option_count;
+        template.reward_count = params.reward_count;
+        template.max_holder_count = params.max_holder_count;
+        template.claim_window = params.claim_window;
+        template.max_total_tickets = params.max_total_tickets;
+
+        // Rounds are started as soon as they are spawned, so they can't be expanded first
+        if get_raffle_size(template.get_create_raffle_params()) > MAX_PERMITTED_DATA_INCREASE { return Err(ErrorCode::RaffleTooBig.into()); }
+
+        // Rules
+        template.duration = params.duration;
+        template.active = true;
+        template.last_raffle = Pubkey::new_from_array(NULL_KEY_ARRAY).clone();
+        template.last_end_date = 0;
+        template.rounds = 0;
+
+        // Proceeds, every round pays the owner everything until payees are set
+        template.payees = Vec::new();
+        template.dust_recipient = template.owner;
+
+        Ok(())
+    }
+
+    // ------------ LOAD TEMPLATE PAYMENT OPTION -------------------------------
+    pub fn load_template_payment_option(
+        ctx: Context<LoadTemplatePaymentOption>,
+        params: LoadPaymentOptionParams,
+    ) -> ProgramResult {
+
+        let template = &mut ctx.accounts.template;
+
+        if params.name.len() > MAX_NAME_LENGTH { return Err(ErrorCode::NameTooLong.into()); }
+        if params.tickets_per_payment > template.max_tickets_per_holder { return Err(ErrorCode::TooManyTicketsPerPayment.into()); }
+        if params.payment_amount < 1  { return Err(ErrorCode::NeedPaymentAmount.into()); }
+        if params.tickets_per_payment < 1  { return Err(ErrorCode::NeedTicketsPerPayemnt.into()); }
+        if params.weight_bps < 1  { return Err(ErrorCode::NeedTicketWeight.into()); }
+        if params.price_steps.len() > 0 { return Err(ErrorCode::BadPriceSchedule.into()); } // Every round has its own dates, only ramps carry over
+        check_price_schedule(&params)?;
+        if template.payment_option_count < (template.payment_options.len() + 1) as u8 { return Err(ErrorCode::TooManyPaymentOptions.into()); }
+        get_mint_decimals(&ctx.accounts.payment_mint)?;
+
+        template.payment_options.push(
+            TicketPaymentOption{
+                name: String::from(params.name),
+                payment_mint: ctx.accounts.payment_mint.key(),
+                payment_amount: params.payment_amount,
+                tickets_per_payment: params.tickets_per_payment,
+                weight_bps: params.weight_bps,
+                end_payment_amount: params.end_payment_amount,
+                price_steps: params.price_steps.clone(),
+                max_purchases: params.max_purchases,
+                payment_tally: 0,
+                amount_paid: 0,
+                cashed_out: false,
+            }
+        );
+
+        Ok(())
+    }
+
+    // ------------ LOAD TEMPLATE REWARD -------------------------------
+    pub fn load_template_reward(
+        ctx: Context<LoadTemplateReward>,
+        params: LoadRewardParams,
+    ) -> ProgramResult {
+
+        let template = &mut ctx.accounts.template;
+
+        // Funds are deposited into the template vault separately, every round takes reward_amount out of it
+        if params.name.len() > MAX_NAME_LENGTH { return Err(ErrorCode::NameTooLong.into()); }
+        if params.reward_amount < 1  { return Err(ErrorCode::NeedRewardAmount.into()); }
+        if template.reward_count < (template.rewards.len() + 1) as u8 { return Err(ErrorCode::TooManyRewards.into()); }
+        get_mint_decimals(&ctx.accounts.reward_mint)?;
+
+        template.rewards.push(
+            Reward{
+                name: String::from(params.name),
+                group_id: params.group_id.clone(),
+                reward_amount: params.reward_amount,
+                reward_mint: ctx.accounts.reward_mint.key(),
+                winner: Pubkey::new_from_array(NULL_KEY_ARRAY).clone(),
+                reward_redeemed: false,
+                sponsor: ctx.accounts.owner.key(),
+                kind: RewardKind::Spl,
+                pot_share_bps: 0,
+                picked_at: 0,
+                rerolls: 0,
+                tier: params.tier,
+            }
+        );
+
+        Ok(())
+    }
+
+    // ------------ UPDATE TEMPLATE -------------------------------
+    pub fn update_template(
+        ctx: Context<UpdateTemplate>,
+        params: UpdateTemplateParams,
+    ) -> ProgramResult {
+
+        let template = &mut ctx.accounts.template;
+
+        if params.duration < 1 || params.duration > MAX_RAFFLE_LENGTH { return Err(ErrorCode::BadTemplateDuration.into()); }
+
+        // Only applies to rounds spawned from now on
+        template.active = params.active;
+        template.duration = params.duration;
+
+        Ok(())
+    }
+
+    // ------------ UPDATE TEMPLATE PAYEES -------------------------------
+    pub fn update_template_payees(
+        ctx: Context<UpdateTemplatePayees>,
+        params: UpdateTemplatePayeesParams,
+    ) -> ProgramResult {
+
+        let template = &mut ctx.accounts.template;
+
+        // Only applies to rounds spawned from now on, a round keeps the split it started with
+        check_payees(&params.payees, &params.dust_recipient)?;
+
+        template.payees = params.payees;
+        template.dust_recipient = params.dust_recipient;
+
+        Ok(())
+    }
+
+    // ------------ WITHDRAW TEMPLATE FUNDS -------------------------------
+    pub fn withdraw_template_funds(
+        ctx: Context<WithdrawTemplateFunds>,
+        params: WithdrawTemplateFundsParams,
+    ) -> ProgramResult {
+
+        let template = &ctx.accounts.template;
+
+        if params.amount < 1 || params.amount > get_token_account(&ctx.accounts.template_vault)?.amount { return Err(ErrorCode::NoMoreSPL.into()); }
+
+        // TX Output
+        let template_key = template.key();
+        let seeds = &[
+            template_key.as_ref(),
+            &[template.nonce],
+        ];
+        let signer = &[&seeds[..]];
+
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.template_vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.owner_vault,
+            &ctx.accounts.keeper,
+            params.amount,
+            signer,
+        )?;
+
+        Ok(())
+    }
+
+    // ------------ SPAWN FROM TEMPLATE -------------------------------
+    pub fn spawn_from_template<'info>(
+        ctx: Context<'_, '_, '_, 'info, SpawnFromTemplate<'info>>,
+    ) -> ProgramResult {
+
+        let template = &mut ctx.accounts.template;
+        let raffle = &mut ctx.accounts.raffle;
+        let master_raffle = &mut ctx.accounts.master_raffle;
+        let bot = ctx.accounts.rng_bot.load()?;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Simple Checks
+        if !template.active { return Err(ErrorCode::TemplateInactive.into()); }
+        if current_time <= template.last_end_date { return Err(ErrorCode::PreviousRoundNotEnded.into()); }
+        if master_raffle.paused { return Err(ErrorCode::RafflePaused.into()); }
+        if template.payment_options.len() < 1 { return Err(ErrorCode::NeedPaymentOption.into()); }
+        if template.rewards.len() < 1 { return Err(ErrorCode::NeedReward.into()); }
+        if master_raffle.raffles.len() + 1 > master_raffle.max_raffles as usize { return Err(ErrorCode::TooManyRaffles.into()); }
+        if bot.request_counter < 1 { return Err(ErrorCode::NeedRngFirst.into()); }
+        if ctx.remaining_accounts.len() < template.rewards.len() * 3 { return Err(ErrorCode::BadTemplateVault.into()); }
+
+        // Check Gatekeeper
+        let (gatekeeper, nonce) = Pubkey::find_program_address(
+            &[raffle.to_account_info().key.as_ref()],
+            ctx.program_id,
+        );
+
+        if &gatekeeper != ctx.accounts.gatekeeper.to_account_info().key {
+            return Err(ErrorCode::BadGatekeeper.into());
+        }
+
+        init_raffle(
+            raffle,
+            master_raffle,
+            template.owner,
+            gatekeeper,
+            nonce,
+            &template.get_create_raffle_params(),
+        );
+
+        // Buying a Ticket
+        for i in 0..template.payment_options.len() {
+            raffle.payment_options.push(template.payment_options[i].clone());
+        }
+
+        // Proceeds
+        raffle.payees = template.payees.clone();
+        raffle.dust_recipient = template.dust_recipient;
+
+        // Fund the rewards, remaining accounts are [reward mint, template vault, reward vault] triples in reward order
+        let template_key = template.key();
+        let seeds = &[
+            template_key.as_ref(),
+            &[template.nonce],
+        ];
+        let signer = &[&seeds[..]];
+        let token_program = ctx.accounts.token_program.key();
+
+        for i in 0..template.rewards.len() {
+            let reward_mint = &ctx.remaining_accounts[i * 3];
+            let template_vault = &ctx.remaining_accounts[i * 3 + 1];
+            let reward_vault = &ctx.remaining_accounts[i * 3 + 2];
+
+            if template.rewards[i].reward_mint != reward_mint.key() { return Err(ErrorCode::BadTemplateVault.into()); }
+            if get_vault_address(&template.keeper, reward_mint.key, &token_program) != template_vault.key() { return Err(ErrorCode::BadTemplateVault.into()); }
+            if get_vault_address(&gatekeeper, reward_mint.key, &token_program) != reward_vault.key() { return Err(ErrorCode::BadTemplateVault.into()); }
+
+            let amount_before = get_token_account(reward_vault)?.amount;
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                template_vault,
+                reward_mint,
+                reward_vault,
+                &ctx.accounts.keeper,
+                template.rewards[i].reward_amount,
+                signer,
+            )?;
+
+            // The round can only pay out what arrived after any transfer fee
+            let mut reward = template.rewards[i].clone();
+            reward.reward_amount = get_received_amount(reward_vault, amount_before)?;
+            raffle.rewards.push(reward);
+        }
+
+        // Rules - the round starts right away
+        raffle.start_date = current_time;
+        raffle.end_date = current_time + template.duration;
+        raffle.ramp_end_date = raffle.end_date;
+
+        // Template
+        template.last_raffle = raffle.key();
+        template.last_end_date = raffle.end_date;
+        template.rounds += 1;
+
+        emit!(RaffleSpawned {
+            template: template.key(),
+            raffle: raffle.key(),
+            round: template.rounds,
+            start_date: raffle.start_date,
+            end_date: raffle.end_date,
+        });
+
+        Ok(())
+    }
+}
+
+// ------------ CREATE MASTER RAFFLE ------------------------
+#[derive(Accounts)]
+#[instruction(params: CreateMasterRaffleParams)]
+pub struct CreateMasterRaffle<'info> {
+    #[account(
+        init,
+        seeds = [
+            MASTER_RAFFLE_SEED,
+            owner.key().as_ref(),
+            &params.index.to_le_bytes(),
+        ],
+        bump,
+        payer = owner,
+        space = get_master_raffle_size(params.clone()),
+    )]
+    pub master_raffle: Account<'info, MasterRaffle>, // Account data, PDA of the owner and index
+
+    // RNG
+    pub oracle: AccountInfo<'info>, // Account that makes the RNG
+    pub vrf_account: AccountInfo<'info>, // Account that actually requests the RNG
+    #[account(
+        init,
+        seeds = [
+            STATE_SEED, 
+            master_raffle.key().as_ref(), 
+            owner.key().as_ref()
+        ],
+        bump,
+        payer = owner,
+    )]
+    pub rng_bot: AccountLoader<'info, RngBot>, // Account that holds the RNG for all child raffles
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Owner needs to pay for the account creation
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Needed for account creation
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct CreateMasterRaffleParams {
+    pub index: u64, // Used to derive the master raffle, lets an owner have more than one
+    pub name: String, // Somthing human readable to call the master
+    pub max_raffles: u16, // Maxiumum amount of raffles this can hold
+    pub bot_name: String, // Name to call the RNG bot
+}
+
+// ------------ REMOVE RAFFLES FROM MASTER -------------------------------
+#[derive(Accounts)]
+pub struct RemoveRafflesFromMaster<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = master_raffle.owner == owner.key()
+    )]
+    pub master_raffle: Account<'info, MasterRaffle>, // Accound data
+
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to remove keys from the Master Raffle
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct RemoveRafflesFromMasterParams {
+    pub raffles_to_remove: Vec<Pubkey>, // The pubkey of raffles to remove - if one is removed accidentally, it can be retrieved.
+}
+
+// ------------ UPDATE MASTER VRF -------------------------------
+#[derive(Accounts)]
+pub struct UpdateMasterRng<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = master_raffle.owner == owner.key()
+    )]
+    pub master_raffle: Account<'info, MasterRaffle>, // Accound data
+    #[account(
+        mut,
+        constraint = rng_bot.key() == master_raffle.rng_bot
+    )]
+    pub rng_bot: AccountLoader<'info, RngBot>, // Must check RNG bot
+
+    pub new_oracle_account: AccountInfo<'info>, // Account that makes the RNG
+    pub new_vrf_account: AccountInfo<'info>, // Account that actually requests the RNG
+
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to remove keys from the Master Raffle
+}
+
+// ------------ PAUSE MASTER RAFFLE -------------------------------
+#[derive(Accounts)]
+pub struct PauseMasterRaffle<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = master_raffle.owner == owner.key()
+    )]
+    pub master_raffle: Account<'info, MasterRaffle>, // Accound data
+
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to pause every raffle
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct PauseMasterRaffleParams {
+    pub paused: bool, // True halts ticket sales in all child raffles, false lets them continue
+}
+
+// ------------ CONFIGURE JACKPOT -------------------------------
+#[derive(Accounts)]
+pub struct ConfigureJackpot<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = master_raffle.owner == owner.key()
+    )]
+    pub master_raffle: Account<'info, MasterRaffle>, // Accound data
+
+    pub jackpot_keeper: AccountInfo<'info>, // PDA that owns the jackpot vault
+
+    pub jackpot_mint: AccountInfo<'info>, // Mint the jackpot is pooled in, SPL Token or Token-2022
+    #[account(
+        constraint = is_associated_vault(&jackpot_vault, jackpot_keeper.key, jackpot_mint.key)
+    )]
+    pub jackpot_vault: AccountInfo<'info>, // SPL vault the jackpot is pooled in
+
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to set up the jackpot
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ConfigureJackpotParams {
+    pub jackpot_bps: u16, // Slice of each new raffle's ticket sales that goes to the jackpot, 10000 is all of it
+    pub jackpot_odds_bps: u16, // Chance a raffle's jackpot reward is won, 10000 is always
+}
+
+// ------------ UPDATE OPERATOR -------------------------------
+#[derive(Accounts)]
+pub struct UpdateOperator<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = master_raffle.owner == owner.key()
+    )]
+    pub master_raffle: Account<'info, MasterRaffle>, // Accound data
+
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to choose the operator
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateOperatorParams {
+    pub operator: Pubkey, // Can grant free tickets in every child raffle
+    pub voucher_authority: Pubkey, // Signs vouchers holders can redeem for free tickets in every child raffle
+}
+
+// ------------ CREATE RAFFLE -------------------------------
+#[derive(Accounts)]
+#[instruction(params: CreateRaffleParams)]
+pub struct CreateRaffle<'info> {
+    #[account(
+        init,
+        seeds = [
+            RAFFLE_SEED,
+            master_raffle.key().as_ref(),
+            &master_raffle.raffle_counter.to_le_bytes(),
+        ],
+        bump,
+        payer = owner,
+        space = std::cmp::min(get_raffle_size(params.clone()), MAX_PERMITTED_DATA_INCREASE),
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data to be created, PDA of the master raffle and its raffle counter, capped at 10KB until expanded
+    pub gatekeeper: AccountInfo<'info>, // Needed to sign for and own all of the SPL vaults
+
+    #[account(
+        mut, 
+        has_one = owner, 
+    )]
+    pub master_raffle: Account<'info, MasterRaffle>, // Parent raffle that holds this as an index as well as has access to the RNG
+    #[account(
+        mut,
+        constraint = rng_bot.key() == master_raffle.rng_bot
+    )]
+    pub rng_bot: AccountLoader<'info, RngBot>, // Must check RNG bot
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Owner pays for the account creation   
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Needed for the account creation
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct CreateRaffleParams {
+    pub name: String, // Something human readable to call the raffle
+    pub max_tickets_per_holder: u8, // Each holder can only have up to 255 tickets per raffle
+    pub payment_option_count: u8, // Amount of payment options to load
+    pub reward_count: u8, // Amount of reward types to load
+    pub max_holder_count: u16, // Max amount of holder per raffle 
+    pub max_sponsor_count: u8, // Max amount of sponsors that can add rewards once the raffle has started
+    pub max_free_tickets: u32, // Max amount of tickets the owner or operator can grant for free
+    pub max_rerolls: u8, // How many times an unclaimed reward can be drawn again, 0 turns rerolls off
+    pub reroll_window: u64, // How long a winner has to claim before the reward can be drawn again (In Seconds)
+    pub claim_window: u64, // How long after the end date winners have to redeem before the owner can (In Seconds), 1 day to 1 year
+    pub max_total_tickets: u32, // Max amount of tickets sold across every payment option, 0 for no limit
+}
+
+// ------------ EXPAND RAFFLE -------------------------------
+#[derive(Accounts)]
+pub struct ExpandRaffle<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Owner pays the rent for the extra space
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Needed to pay the rent
+}
+
+// ------------ LOAD PAYMENT OPTION -------------------------------
+#[derive(Accounts)]
+pub struct LoadPaymentOption<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    #[account(
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Used as a check
+
+    pub payment_mint: AccountInfo<'info>, // SPL Token or Token-2022 mint the option is paid in
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&payment_vault, gatekeeper.key, payment_mint.key)
+    )]
+    pub payment_vault: AccountInfo<'info>, // SPL vault that will keep the payment SPLs
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to load a payment option
+    #[account(constraint = is_token_program(token_program.key))]
+    pub token_program: AccountInfo<'info>, // Needed to TX SPLs
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct LoadPaymentOptionParams {
+    pub name: String, // Somthing human readable for the front end
+    pub payment_amount: u64, // Amount of the SLP for this payment option
+    pub tickets_per_payment: u8, // Number of tickets per purchase with this option
+    pub weight_bps: u16, // How much each ticket counts in the draw, 10000 is 1x and 15000 is 1.5x
+    pub end_payment_amount: u64, // Price at the end date, ramps linearly from payment_amount at the start date, 0 keeps the price fixed
+    pub price_steps: Vec<PriceStep>, // From starts_at on, the price is the step's payment_amount, in order and up to 4, can not be used with a ramp
+    pub max_purchases: u64, // How many times this option can be bought, 0 for no limit
+}
+
+// ------------ LOAD REWARD -------------------------------
+#[derive(Accounts)]
+pub struct LoadReward<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    #[account(
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Used as a check
+
+    pub reward_mint: AccountInfo<'info>, // SPL Token or Token-2022 mint of the reward
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&reward_vault, gatekeeper.key, reward_mint.key)
+    )]
+    pub reward_vault: AccountInfo<'info>, // SPL vault owned by the gatekeeper
+
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&owner_vault, owner.key, reward_mint.key)
+    )]
+    pub owner_vault: AccountInfo<'info>, // SPL vault of owner -> gatekeeper's vault
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to load a reward
+    #[account(constraint = is_token_program(token_program.key))]
+    pub token_program: AccountInfo<'info>, // Needed for TX 
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct LoadRewardParams {
+    pub name: String, // Something human readable for the frontend to show
+    pub group_id: Pubkey, // All other Rewards with the same ID will be treated as one large reward, pass in a unique key or a zero'd key to let it be on it's own
+    pub reward_amount: u64, // How many of the SPL is awarded (for an NFT this would be 1)
+    pub tier: u8, // 0 is the grand prize, every reward in a tier is drawn before the next one
+}
+
+// ------------ LOAD SOL REWARD -------------------------------
+#[derive(Accounts)]
+pub struct LoadSolReward<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    #[account(
+        mut,
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Holds the SOL until it is redeemed
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to load a reward
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Needed to TX SOL
+}
+
+// ------------ LOAD POT REWARD -------------------------------
+#[derive(Accounts)]
+pub struct LoadPotReward<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to load a reward
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct LoadPotRewardParams {
+    pub name: String, // Something human readable for the frontend to show
+    pub group_id: Pubkey, // Same as LoadRewardParams
+    pub payment_mint: Pubkey, // Mint of the payment options that make up the pot
+    pub pot_share_bps: u16, // Share of everything collected in payment_mint, 10000 is all of it
+    pub tier: u8, // Same as LoadRewardParams
+}
+
+// ------------ LOAD JACKPOT REWARD -------------------------------
+#[derive(Accounts)]
+pub struct LoadJackpotReward<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        has_one = master_raffle,
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+    pub master_raffle: Account<'info, MasterRaffle>, // Holds the jackpot
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to load a reward
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct LoadJackpotRewardParams {
+    pub name: String, // Something human readable for the frontend to show
+    pub tier: u8, // Same as LoadRewardParams
+}
+
+// ------------ UPDATE SPONSORS -------------------------------
+#[derive(Accounts)]
+pub struct UpdateSponsors<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to choose sponsors
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateSponsorsParams {
+    pub sponsors: Vec<Pubkey>, // Replaces the current allowlist, up to max_sponsor_count
+}
+
+// ------------ UPDATE PAYEES -------------------------------
+#[derive(Accounts)]
+pub struct UpdatePayees<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to split the proceeds
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdatePayeesParams {
+    pub payees: Vec<Payee>, // Replaces the current split, up to 8 and adding up to 10000 bps, empty pays the owner everything
+    pub dust_recipient: Pubkey, // One of the payees, gets whatever is left after rounding
+}
+
+// ------------ ADD REWARD -------------------------------
+#[derive(Accounts)]
+pub struct AddReward<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    #[account(
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Used as a check
+
+    pub reward_mint: AccountInfo<'info>, // SPL Token or Token-2022 mint of the reward
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&reward_vault, gatekeeper.key, reward_mint.key)
+    )]
+    pub reward_vault: AccountInfo<'info>, // SPL vault owned by the gatekeeper
+
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&sponsor_vault, sponsor.key, reward_mint.key)
+    )]
+    pub sponsor_vault: AccountInfo<'info>, // SPL vault of sponsor -> gatekeeper's vault
+
+    // Signers
+    #[account(mut)]
+    pub sponsor: Signer<'info>, // The owner or an allowlisted sponsor, pays for any extra space
+    pub owner: AccountInfo<'info>, // Used as a check
+    #[account(constraint = is_token_program(token_program.key))]
+    pub token_program: AccountInfo<'info>, // Needed for TX 
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Needed to pay for the extra space
+}
+
+// ------------ START RAFFLE -------------------------------
+#[derive(Accounts)]
+pub struct StartRaffle<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+ 
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to start this
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct StartRaffleParams {
+    pub start_date: Option<u64>, // Unix time, when tickets go on sale, defaults to the Unix date when StartRaffle is called
+    pub end_date: u64, // Unix time, needs to be larger that the start date
+}
+
+// ------------ UPDATE END DATE -------------------------------
+#[derive(Accounts)]
+pub struct UpdateEndDate<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to change the end date
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateEndDateParams {
+    pub end_date: u64, // Unix time, can not be more than 3 years from the start date
+}
+
+// ------------ PAUSE RAFFLE -------------------------------
+#[derive(Accounts)]
+pub struct PauseRaffle<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to pause
+}
+
+// ------------ RESUME RAFFLE -------------------------------
+#[derive(Accounts)]
+pub struct ResumeRaffle<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to resume
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ResumeRaffleParams {
+    pub extend_end_date: bool, // Pushes the end date back by how long the raffle was paused
+}
+
+// ------------ BUY TICKET -------------------------------
+#[derive(Accounts)]
+pub struct BuyTickets<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        has_one = master_raffle,
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+    pub master_raffle: Account<'info, MasterRaffle>, // Used to check for an emergency pause
+
+    #[account(
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Here to check
+
+    pub payment_mint: AccountInfo<'info>, // Mint of the payment option, checked against it
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&payment_vault, gatekeeper.key, payment_mint.key)
+    )]
+    pub payment_vault: AccountInfo<'info>, // Vault owned by the gatekeeper to store the token
+
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&holder_vault, holder.key, payment_mint.key)
+    )]
+    pub holder_vault: AccountInfo<'info>, // Holds the token to buy the ticket
+
+    // Signers
+    #[account(mut)]
+    pub holder: Signer<'info>, // Person buying the tickets
+    #[account(mut)]
+    pub owner: AccountInfo<'info>, // Used as a check    
+    #[account(constraint = is_token_program(token_program.key))]
+    pub token_program: AccountInfo<'info>, // Used to TX
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct BuyTicketsParam {
+    pub payment_option_index: u8, // Index of the payment option used
+}
+
+// ------------ GRANT TICKETS -------------------------------
+#[derive(Accounts)]
+pub struct GrantTickets<'info> {
+    #[account(
+        mut, 
+        has_one = master_raffle,
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+    pub master_raffle: Account<'info, MasterRaffle>, // Holds the operator, also used to check for an emergency pause
+
+    // Signers
+    pub authority: Signer<'info>, // The owner or the master's operator
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct TicketGrant {
+    pub holder: Pubkey, // Who gets the tickets, does not need to sign
+    pub tickets: u8, // How many tickets they get
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct GrantTicketsParams {
+    pub grants: Vec<TicketGrant>, // Tickets to credit, all of them or none are granted
+}
+
+// ------------ REDEEM VOUCHER -------------------------------
+#[derive(Accounts)]
+#[instruction(params: RedeemVoucherParams)]
+pub struct RedeemVoucher<'info> {
+    #[account(
+        mut, 
+        has_one = master_raffle,
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+    pub master_raffle: Account<'info, MasterRaffle>, // Holds the voucher authority, also used to check for an emergency pause
+
+    #[account(
+        init_if_needed,
+        seeds = [
+            VOUCHER_SEED,
+            raffle.key().as_ref(),
+            &(params.nonce / VOUCHERS_PER_BITMAP).to_le_bytes(),
+        ],
+        bump,
+        payer = holder,
+        space = 8 + size_of::<VoucherBitmap>(),
+    )]
+    pub voucher_bitmap: Account<'info, VoucherBitmap>, // Tracks used nonces, PDA of the raffle and nonce / VOUCHERS_PER_BITMAP
+
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>, // Used to find the ed25519 signature check
+
+    // Signers
+    #[account(mut)]
+    pub holder: Signer<'info>, // Person the voucher was issued to, pays for a new bitmap
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Needed for the bitmap creation
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct RedeemVoucherParams {
+    pub tickets: u8, // How many tickets the voucher is for
+    pub nonce: u64, // Unique per raffle, picked by whoever issues the vouchers
+}
+
+// ------------ RNG CB ---------------------------
+#[derive(Accounts)]
+pub struct RngCallback<'info> {
+    #[account(mut)]
+    pub state: AccountLoader<'info, RngBot>, // The account to transfer the new RNG to
+    pub vrf_account: AccountInfo<'info>, // Account with new RNG from Switchboard
+}
+
+// ------------ PICK WINNER ---------------------------
+#[derive(Accounts)]
+pub struct PickWinner<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        has_one = master_raffle,
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+    #[account(mut)]
+    pub master_raffle: Account<'info, MasterRaffle>, // Holds the jackpot
+
+    #[account(
+        mut, 
+        constraint = rng_bot.key() == raffle.rng_bot,
+    )]
+    pub rng_bot: AccountLoader<'info, RngBot>, // Holdes the RNG value
+
+    #[account(mut)]
+    pub owner: AccountInfo<'info>, // No need for this to be signed, anyone could call it
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct PickWinnerParams {
+    pub reward_index: Option<u8>, // Reward to draw, the owner has to sign for this, defaults to the first one without a winner
+}
+
+// ------------ REROLL REWARD ---------------------------
+#[derive(Accounts)]
+pub struct RerollReward<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    #[account(
+        mut, 
+        constraint = rng_bot.key() == raffle.rng_bot,
+    )]
+    pub rng_bot: AccountLoader<'info, RngBot>, // Holdes the RNG value
+
+    pub owner: AccountInfo<'info>, // No need for this to be signed, anyone could call it
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct RerollRewardParams {
+    pub reward_index: Option<u8>, // Reward to draw again, the owner has to sign for this, defaults to the first one that can be rerolled
+}
+
+// ------------ DISTRIBUTE PROCEEDS -------------------------
+#[derive(Accounts)]
+pub struct DistributeProceeds<'info> {
+    #[account(
+        mut, 
+        has_one = master_raffle,
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+    #[account(mut)]
+    pub master_raffle: Account<'info, MasterRaffle>, // Holds the jackpot
+
+    #[account(
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Owner of the payment vault needed to sign the TX
+
+    pub payment_mint: AccountInfo<'info>, // Mint being paid out
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&payment_vault, gatekeeper.key, payment_mint.key)
+    )]
+    pub payment_vault: AccountInfo<'info>, // SPL vault users paid into, owned by the gatekeeper
+
+    // Anyone can call this, the payees' vaults are checked against the raffle
+    #[account(constraint = is_token_program(token_program.key))]
+    pub token_program: AccountInfo<'info>, // Used to TX
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct DistributeProceedsParams {
+    pub amount: Option<u64>, // How much to split between the payees, defaults to everything still owed for the mint
+}
+
+// ------------ SWEEP VAULT -------------------------
+#[derive(Accounts)]
+pub struct SweepVault<'info> {
+    #[account(
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    #[account(
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Owner of the vault needed to sign the TX
+
+    pub mint: AccountInfo<'info>, // Mint of the vault
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&vault, gatekeeper.key, mint.key)
+    )]
+    pub vault: AccountInfo<'info>, // Any SPL vault of the gatekeeper
+
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&owner_vault, owner.key, mint.key)
+    )]
+    pub owner_vault: AccountInfo<'info>, // SPL vault owned by owner
+
+    // Signers
+    pub owner: Signer<'info>, // Only the owner gets what was sent by mistake
+    #[account(constraint = is_token_program(token_program.key))]
+    pub token_program: AccountInfo<'info>, // Used to TX
+}
+
+// ------------ REDEEM REWARD -------------------------
+#[derive(Accounts)]
+pub struct RedeemReward<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Has the data
+
+    #[account(
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Needs to sign the TX
+
+    pub reward_mint: AccountInfo<'info>, // Mint of the reward, checked against it
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&reward_vault, gatekeeper.key, reward_mint.key)
+    )]
+    pub reward_vault: AccountInfo<'info>, // SPL vault owned by the gatekeeper
+
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&winner_vault, winner.key, reward_mint.key)
+    )]
+    pub winner_vault: AccountInfo<'info>, // SPL vault owned by the winner
+
+    // Signers
+    #[account(mut)]
+    pub winner: Signer<'info>, // Needs to sign to redeem
+    pub owner: AccountInfo<'info>, // Used for a check
+    #[account(constraint = is_token_program(token_program.key))]
+    pub token_program: AccountInfo<'info>, // Used to TX
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct RedeemRewardParams {
+    pub reward_index: Option<u8>, // Reward to redeem, defaults to the first one the winner can redeem
+}
+
+// ------------ REDEEM REWARDS -------------------------
+#[derive(Accounts)]
+pub struct RedeemRewards<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Has the data
+
+    #[account(
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Needs to sign the TX
+
+    // Signers
+    #[account(mut)]
+    pub winner: Signer<'info>, // Needs to sign to redeem
+    pub owner: AccountInfo<'info>, // Used for a check
+    #[account(constraint = is_token_program(token_program.key))]
+    pub token_program: AccountInfo<'info>, // Used to TX, every vault has to be under it
+}
+
+// ------------ RECLAIM EXPIRED REWARD -------------------------
+#[derive(Accounts)]
+pub struct ReclaimExpiredReward<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        has_one = master_raffle,
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Has the data
+    #[account(mut)]
+    pub master_raffle: Account<'info, MasterRaffle>, // Takes back an unclaimed jackpot
+
+    #[account(
+        mut,
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Holds the SOL and needs to sign the TX
+    pub jackpot_keeper: AccountInfo<'info>, // Signs for a jackpot in a mint the master no longer pools, checked when used
+
+    pub reward_mint: AccountInfo<'info>, // Mint of the reward, checked when used
+    #[account(mut)]
+    pub reward_vault: AccountInfo<'info>, // SPL vault owned by the gatekeeper (or the jackpot keeper), checked when used
+    #[account(mut)]
+    pub owner_vault: AccountInfo<'info>, // SPL vault owned by owner, checked when used
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner can take back expired rewards
+    #[account(constraint = is_token_program(token_program.key))]
+    pub token_program: AccountInfo<'info>, // Used to TX
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Used to TX SOL
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ReclaimExpiredRewardParams {
+    pub reward_index: u8, // Index of the expired reward
+}
+
+// ------------ FUND CRANK FEE -------------------------
+#[derive(Accounts)]
+pub struct FundCrankFee<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    #[account(
+        mut,
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Holds the fees until they are paid out
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to fund the crank
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Needed to TX SOL
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct FundCrankFeeParams {
+    pub crank_fee: u64, // Lamports paid per distributed reward, 0 turns the fee off
+    pub amount: u64, // Lamports to add to the crank fee balance
+}
+
+// ------------ RECLAIM CRANK FEE -------------------------
+#[derive(Accounts)]
+pub struct ReclaimCrankFee<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Account data
+
+    #[account(
+        mut,
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Holds the fees and needs to sign the TX
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to take the fees back
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Needed to TX SOL
 }
 
-// ------------ CREATE MASTER RAFFLE ------------------------
+// ------------ DISTRIBUTE REWARD -------------------------
 #[derive(Accounts)]
-#[instruction(params: CreateMasterRaffleParams)]
-pub struct CreateMasterRaffle<'info> {
-    #[account(zero)]
-    pub master_raffle: Account<'info, MasterRaffle>, // Account data
+pub struct DistributeReward<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Has the data
 
-    // RNG
-    pub oracle: AccountInfo<'info>, // Account that makes the RNG
-    pub vrf_account: AccountInfo<'info>, // Account that actually requests the RNG
     #[account(
-        init,
-        seeds = [
-            STATE_SEED, 
-            master_raffle.key().as_ref(), 
-            owner.key().as_ref()
-        ],
-        payer = owner,
-        bump = params.bot_bump,
+        mut,
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
     )]
-    pub rng_bot: AccountLoader<'info, RngBot>, // Account that holds the RNG for all child raffles
+    gatekeeper: AccountInfo<'info>, // Needs to sign the TX, also pays the crank fee
+
+    pub reward_mint: AccountInfo<'info>, // Needed to create the winner's vault
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&reward_vault, gatekeeper.key, reward_mint.key)
+    )]
+    pub reward_vault: AccountInfo<'info>, // SPL vault owned by the gatekeeper
+
+    pub winner: AccountInfo<'info>, // Does not need to sign, checked against the reward
+    #[account(mut)]
+    pub winner_vault: AccountInfo<'info>, // Winner's associated token account, created if it does not exist
 
     // Signers
     #[account(mut)]
-    pub owner: Signer<'info>, // Owner needs to pay for the account creation
+    pub payer: Signer<'info>, // Anyone can distribute, they get the crank fee
+    pub owner: AccountInfo<'info>, // Used for a check
+    #[account(constraint = is_token_program(token_program.key))]
+    pub token_program: AccountInfo<'info>, // Used to TX, checked since no owner or winner signs
+    #[account(address = spl_associated_token_account::ID)]
+    pub associated_token_program: AccountInfo<'info>, // Used to create the winner's vault
     #[account(address = solana_program::system_program::ID)]
-    pub system_program: AccountInfo<'info>, // Needed for account creation
+    pub system_program: AccountInfo<'info>, // Used to TX SOL and create the winner's vault
+    #[account(address = solana_program::sysvar::rent::ID)]
+    pub rent: AccountInfo<'info>, // Needed to create the winner's vault
 }
 #[derive(Clone, AnchorSerialize, AnchorDeserialize)]
-pub struct CreateMasterRaffleParams {
-    pub name: String, // Somthing human readable to call the master
-    pub max_raffles: u16, // Maxiumum amount of raffles this can hold
-    pub bot_bump: u8, // Bot bump
-    pub bot_name: String, // Name to call the RNG bot
+pub struct DistributeRewardParams {
+    pub reward_index: Option<u8>, // Reward to distribute, defaults to the first one the winner can redeem
 }
 
-// ------------ REMOVE RAFFLES FROM MASTER -------------------------------
+// ------------ DISTRIBUTE SOL REWARD -------------------------
 #[derive(Accounts)]
-pub struct RemoveRafflesFromMaster<'info> {
+pub struct DistributeSolReward<'info> {
     #[account(
         mut, 
         has_one = owner, 
-        constraint = master_raffle.owner == owner.key()
+        constraint = raffle.owner == owner.key()
     )]
-    pub master_raffle: Account<'info, MasterRaffle>, // Accound data
+    pub raffle: Account<'info, Raffle>, // Has the data
+
+    #[account(
+        mut,
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Holds the SOL and needs to sign the TX, also pays the crank fee
 
     #[account(mut)]
-    pub owner: Signer<'info>, // Only the owner should be able to remove keys from the Master Raffle
+    pub winner: AccountInfo<'info>, // Does not need to sign, checked against the reward
+
+    // Signers
+    #[account(mut)]
+    pub payer: Signer<'info>, // Anyone can distribute, they get the crank fee
+    pub owner: AccountInfo<'info>, // Used for a check
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Used to TX SOL
 }
 #[derive(Clone, AnchorSerialize, AnchorDeserialize)]
-pub struct RemoveRafflesFromMasterParams {
-    pub raffles_to_remove: Vec<Pubkey>, // The pubkey of raffles to remove - if one is removed accidentally, it can be retrieved.
+pub struct DistributeSolRewardParams {
+    pub reward_index: Option<u8>, // Reward to distribute, defaults to the first SOL reward the winner can redeem
 }
 
-// ------------ UPDATE MASTER VRF -------------------------------
+// ------------ DISTRIBUTE JACKPOT -------------------------
 #[derive(Accounts)]
-pub struct UpdateMasterRng<'info> {
+pub struct DistributeJackpot<'info> {
     #[account(
         mut, 
         has_one = owner, 
-        constraint = master_raffle.owner == owner.key()
+        has_one = master_raffle,
+        constraint = raffle.owner == owner.key()
     )]
-    pub master_raffle: Account<'info, MasterRaffle>, // Accound data
+    pub raffle: Account<'info, Raffle>, // Has the data
+    #[account(mut)]
+    pub master_raffle: Account<'info, MasterRaffle>, // Holds the jackpot
+
     #[account(
         mut,
-        constraint = rng_bot.key() == master_raffle.rng_bot
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
     )]
-    pub rng_bot: AccountLoader<'info, RngBot>, // Must check RNG bot
+    gatekeeper: AccountInfo<'info>, // Pays the crank fee
 
-    pub new_oracle_account: AccountInfo<'info>, // Account that makes the RNG
-    pub new_vrf_account: AccountInfo<'info>, // Account that actually requests the RNG
+    #[account(
+        seeds = [JACKPOT_SEED, master_raffle.to_account_info().key.as_ref()],
+        bump = master_raffle.jackpot_nonce,
+    )]
+    jackpot_keeper: AccountInfo<'info>, // Needs to sign the TX
+
+    pub jackpot_mint: AccountInfo<'info>, // Needed to create the winner's vault
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&jackpot_vault, jackpot_keeper.key, jackpot_mint.key)
+    )]
+    pub jackpot_vault: AccountInfo<'info>, // SPL vault owned by the jackpot keeper
 
+    pub winner: AccountInfo<'info>, // Does not need to sign, checked against the reward
     #[account(mut)]
-    pub owner: Signer<'info>, // Only the owner should be able to remove keys from the Master Raffle
+    pub winner_vault: AccountInfo<'info>, // Winner's associated token account, created if it does not exist
+
+    // Signers
+    #[account(mut)]
+    pub payer: Signer<'info>, // Anyone can distribute, they get the crank fee
+    pub owner: AccountInfo<'info>, // Used for a check
+    #[account(constraint = is_token_program(token_program.key))]
+    pub token_program: AccountInfo<'info>, // Used to TX, checked since no owner or winner signs
+    #[account(address = spl_associated_token_account::ID)]
+    pub associated_token_program: AccountInfo<'info>, // Used to create the winner's vault
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Used to TX SOL and create the winner's vault
+    #[account(address = solana_program::sysvar::rent::ID)]
+    pub rent: AccountInfo<'info>, // Needed to create the winner's vault
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct DistributeJackpotParams {
+    pub reward_index: Option<u8>, // Reward to distribute, defaults to the raffle's jackpot reward
 }
 
-// ------------ CREATE RAFFLE -------------------------------
+// ------------ REDEEM SOL REWARD -------------------------
 #[derive(Accounts)]
-#[instruction(params: CreateRaffleParams)]
-pub struct CreateRaffle<'info> {
-    #[account(zero)]
-    pub raffle: Account<'info, Raffle>, // Account data to be created
-    pub gatekeeper: AccountInfo<'info>, // Needed to sign for and own all of the SPL vaults
-
+pub struct RedeemSolReward<'info> {
     #[account(
         mut, 
         has_one = owner, 
+        constraint = raffle.owner == owner.key()
     )]
-    pub master_raffle: Account<'info, MasterRaffle>, // Parent raffle that holds this as an index as well as has access to the RNG
+    pub raffle: Account<'info, Raffle>, // Has the data
+
     #[account(
         mut,
-        constraint = rng_bot.key() == master_raffle.rng_bot
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
     )]
-    pub rng_bot: AccountLoader<'info, RngBot>, // Must check RNG bot
+    gatekeeper: AccountInfo<'info>, // Holds the SOL and needs to sign the TX
 
     // Signers
     #[account(mut)]
-    pub owner: Signer<'info>, // Owner pays for the account creation   
+    pub winner: Signer<'info>, // Needs to sign to redeem
+    pub owner: AccountInfo<'info>, // Used for a check
     #[account(address = solana_program::system_program::ID)]
-    pub system_program: AccountInfo<'info>, // Needed for the account creation
+    pub system_program: AccountInfo<'info>, // Used to TX SOL
 }
 #[derive(Clone, AnchorSerialize, AnchorDeserialize)]
-pub struct CreateRaffleParams {
-    pub nonce: u8, // Nonce for the gatekeeper
-    pub name: String, // Something human readable to call the raffle
-    pub max_tickets_per_holder: u8, // Each holder can only have up to 255 tickets per raffle
-    pub payment_option_count: u8, // Amount of payment options to load
-    pub reward_count: u8, // Amount of reward types to load
-    pub max_holder_count: u16, // Max amount of holder per raffle 
+pub struct RedeemSolRewardParams {
+    pub reward_index: Option<u8>, // Reward to redeem, defaults to the first SOL reward the winner can redeem
 }
 
-// ------------ LOAD PAYMENT OPTION -------------------------------
+// ------------ REDEEM JACKPOT -------------------------
 #[derive(Accounts)]
-pub struct LoadPaymentOption<'info> {
+pub struct RedeemJackpot<'info> {
     #[account(
         mut, 
         has_one = owner, 
+        has_one = master_raffle,
         constraint = raffle.owner == owner.key()
     )]
-    pub raffle: Account<'info, Raffle>, // Account data
+    pub raffle: Account<'info, Raffle>, // Has the data
+    #[account(mut)]
+    pub master_raffle: Account<'info, MasterRaffle>, // Holds the jackpot
 
     #[account(
-        seeds = [raffle.to_account_info().key.as_ref()],
-        bump = raffle.nonce,
+        seeds = [JACKPOT_SEED, master_raffle.to_account_info().key.as_ref()],
+        bump = master_raffle.jackpot_nonce,
     )]
-    gatekeeper: AccountInfo<'info>, // Used as a check
+    jackpot_keeper: AccountInfo<'info>, // Needs to sign the TX
 
+    pub jackpot_mint: AccountInfo<'info>, // Mint of the jackpot, checked against the reward
     #[account(
         mut, 
-        constraint = gatekeeper.key == &payment_vault.owner 
-        && get_associated_token_address(&gatekeeper.key(), &payment_vault.mint) == payment_vault.key()
+        constraint = is_associated_vault(&jackpot_vault, jackpot_keeper.key, jackpot_mint.key)
     )]
-    pub payment_vault: Account<'info, TokenAccount>, // SPL vault that will keep the payment SPLs
+    pub jackpot_vault: AccountInfo<'info>, // SPL vault owned by the jackpot keeper
+
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&winner_vault, winner.key, jackpot_mint.key)
+    )]
+    pub winner_vault: AccountInfo<'info>, // SPL vault owned by the winner
 
     // Signers
     #[account(mut)]
-    pub owner: Signer<'info>, // Only the owner should be able to load a payment option
-    pub token_program: AccountInfo<'info>, // Needed to TX SPLs
+    pub winner: Signer<'info>, // Needs to sign to redeem
+    pub owner: AccountInfo<'info>, // Used for a check
+    #[account(constraint = is_token_program(token_program.key))]
+    pub token_program: AccountInfo<'info>, // Used to TX
 }
 #[derive(Clone, AnchorSerialize, AnchorDeserialize)]
-pub struct LoadPaymentOptionParams {
-    pub name: String, // Somthing human readable for the front end
-    pub payment_amount: u64, // Amount of the SLP for this payment option
-    pub tickets_per_payment: u8, // Number of tickets per purchase with this option
+pub struct RedeemJackpotParams {
+    pub reward_index: Option<u8>, // Reward to redeem, defaults to the raffle's jackpot reward
 }
 
-// ------------ LOAD REWARD -------------------------------
+// ------------ PROGRAMMABLE NFT -------------------------
+// Everything Token Metadata's transfer needs besides the vaults, their owners and the payer
 #[derive(Accounts)]
-pub struct LoadReward<'info> {
+pub struct ProgrammableNft<'info> {
+    #[account(mut)]
+    pub metadata: AccountInfo<'info>, // Metadata of the mint, checked when used
+    pub edition: AccountInfo<'info>, // Master edition of the mint, checked when used
+    #[account(mut)]
+    pub owner_token_record: AccountInfo<'info>, // Token record of the vault the NFT leaves, checked when used
+    #[account(mut)]
+    pub destination_token_record: AccountInfo<'info>, // Token record of the vault the NFT goes to, created if it does not exist
+    pub authorization_rules_program: AccountInfo<'info>, // Token Auth Rules, or Token Metadata when the NFT has no rule set
+    pub authorization_rules: AccountInfo<'info>, // Rule set on the metadata, or Token Metadata when it has none
+
+    #[account(address = token_metadata::ID)]
+    pub token_metadata_program: AccountInfo<'info>, // Moves the NFT
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: AccountInfo<'info>, // Token Metadata checks how it was called
+    #[account(address = anchor_spl::token::ID)]
+    pub token_program: AccountInfo<'info>, // Programmable NFTs are always SPL Token
+    #[account(address = spl_associated_token_account::ID)]
+    pub associated_token_program: AccountInfo<'info>, // Used to create the destination vault
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Used to create the destination vault and token record
+}
+
+// ------------ LOAD PNFT REWARD -------------------------
+#[derive(Accounts)]
+pub struct LoadPnftReward<'info> {
     #[account(
         mut, 
         has_one = owner, 
@@ -691,199 +3646,358 @@ pub struct LoadReward<'info> {
         seeds = [raffle.to_account_info().key.as_ref()],
         bump = raffle.nonce,
     )]
-    gatekeeper: AccountInfo<'info>, // Used as a check
+    gatekeeper: AccountInfo<'info>, // Holds the NFT until it is redeemed
 
+    pub reward_mint: AccountInfo<'info>, // Mint of the programmable NFT
     #[account(
         mut, 
-        constraint = gatekeeper.key == &reward_vault.owner 
-        && get_associated_token_address(&gatekeeper.key(), &reward_vault.mint) == reward_vault.key()
+        constraint = reward_vault.key() == get_vault_address(gatekeeper.key, reward_mint.key, &anchor_spl::token::ID)
     )]
-    pub reward_vault: Account<'info, TokenAccount>, // SPL vault owned by the gatekeeper
+    pub reward_vault: AccountInfo<'info>, // Gatekeeper's associated token account, created by Token Metadata if it does not exist
 
     #[account(
         mut, 
-        constraint = owner.key == &owner_vault.owner 
-        && owner_vault.mint == reward_vault.mint
-        && get_associated_token_address(&owner.key(), &reward_vault.mint) == owner_vault.key()
+        constraint = is_associated_vault(&owner_vault, owner.key, reward_mint.key)
     )]
-    pub owner_vault: Account<'info, TokenAccount>, // SPL vault of owner -> gatekeeper's vault
+    pub owner_vault: AccountInfo<'info>, // Owner's vault holding the NFT
+
+    pub pnft: ProgrammableNft<'info>, // Token Metadata accounts
 
     // Signers
     #[account(mut)]
-    pub owner: Signer<'info>, // Only the owner should be able to load a reward
-    pub token_program: AccountInfo<'info>, // Needed for TX 
+    pub owner: Signer<'info>, // Only the owner should be able to load a reward, pays for the gatekeeper's vault
+}
+
+// ------------ REDEEM PNFT REWARD -------------------------
+#[derive(Accounts)]
+pub struct RedeemPnftReward<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = raffle.owner == owner.key()
+    )]
+    pub raffle: Account<'info, Raffle>, // Has the data
+
+    #[account(
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Needs to sign the TX
+
+    pub reward_mint: AccountInfo<'info>, // Mint of the programmable NFT, checked against the reward
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&reward_vault, gatekeeper.key, reward_mint.key)
+    )]
+    pub reward_vault: AccountInfo<'info>, // SPL vault owned by the gatekeeper
+
+    #[account(
+        mut, 
+        constraint = winner_vault.key() == get_vault_address(winner.key, reward_mint.key, &anchor_spl::token::ID)
+    )]
+    pub winner_vault: AccountInfo<'info>, // Winner's associated token account, created by Token Metadata if it does not exist
+
+    pub pnft: ProgrammableNft<'info>, // Token Metadata accounts
+
+    // Signers
+    #[account(mut)]
+    pub winner: Signer<'info>, // Needs to sign to redeem, pays for their vault and token record
+    pub owner: AccountInfo<'info>, // Used for a check
 }
 #[derive(Clone, AnchorSerialize, AnchorDeserialize)]
-pub struct LoadRewardParams {
-    pub name: String, // Something human readable for the frontend to show
-    pub group_id: Pubkey, // All other Rewards with the same ID will be treated as one large reward, pass in a unique key or a zero'd key to let it be on it's own
-    pub reward_amount: u64, // How many of the SPL is awarded (for an NFT this would be 1)
+pub struct RedeemPnftRewardParams {
+    pub reward_index: Option<u8>, // Reward to redeem, defaults to the first programmable NFT the winner can redeem
 }
 
-
-// ------------ START RAFFLE -------------------------------
+// ------------ DISTRIBUTE PNFT REWARD -------------------------
 #[derive(Accounts)]
-pub struct StartRaffle<'info> {
+pub struct DistributePnftReward<'info> {
     #[account(
         mut, 
         has_one = owner, 
         constraint = raffle.owner == owner.key()
     )]
-    pub raffle: Account<'info, Raffle>, // Account data
+    pub raffle: Account<'info, Raffle>, // Has the data
 
- 
+    #[account(
+        mut,
+        seeds = [raffle.to_account_info().key.as_ref()],
+        bump = raffle.nonce,
+    )]
+    gatekeeper: AccountInfo<'info>, // Needs to sign the TX, also pays the crank fee
+
+    pub reward_mint: AccountInfo<'info>, // Mint of the programmable NFT, checked against the reward
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&reward_vault, gatekeeper.key, reward_mint.key)
+    )]
+    pub reward_vault: AccountInfo<'info>, // SPL vault owned by the gatekeeper
+
+    pub winner: AccountInfo<'info>, // Does not need to sign, checked against the reward
+    #[account(
+        mut, 
+        constraint = winner_vault.key() == get_vault_address(winner.key, reward_mint.key, &anchor_spl::token::ID)
+    )]
+    pub winner_vault: AccountInfo<'info>, // Winner's associated token account, created by Token Metadata if it does not exist
+
+    pub pnft: ProgrammableNft<'info>, // Token Metadata accounts
+
+    // Signers
     #[account(mut)]
-    pub owner: Signer<'info>, // Only the owner should be able to start this
+    pub payer: Signer<'info>, // Anyone can distribute, they pay for the winner's vault and token record and get the crank fee
+    pub owner: AccountInfo<'info>, // Used for a check
 }
 #[derive(Clone, AnchorSerialize, AnchorDeserialize)]
-pub struct StartRaffleParams {
-    pub end_date: u64, // Unix time, needs to be larger that the Unix date when StartRaffle is called
+pub struct DistributePnftRewardParams {
+    pub reward_index: Option<u8>, // Reward to distribute, defaults to the first programmable NFT the winner can redeem
 }
 
-// ------------ BUY TICKET -------------------------------
+// ------------ RECLAIM EXPIRED PNFT REWARD -------------------------
 #[derive(Accounts)]
-pub struct BuyTickets<'info> {
+pub struct ReclaimExpiredPnftReward<'info> {
     #[account(
         mut, 
         has_one = owner, 
         constraint = raffle.owner == owner.key()
     )]
-    pub raffle: Account<'info, Raffle>, // Account data
+    pub raffle: Account<'info, Raffle>, // Has the data
 
     #[account(
         seeds = [raffle.to_account_info().key.as_ref()],
         bump = raffle.nonce,
     )]
-    gatekeeper: AccountInfo<'info>, // Here to check
+    gatekeeper: AccountInfo<'info>, // Needs to sign the TX
+
+    pub reward_mint: AccountInfo<'info>, // Mint of the programmable NFT, checked against the reward
+    #[account(
+        mut, 
+        constraint = is_associated_vault(&reward_vault, gatekeeper.key, reward_mint.key)
+    )]
+    pub reward_vault: AccountInfo<'info>, // SPL vault owned by the gatekeeper
+
+    #[account(
+        mut, 
+        constraint = owner_vault.key() == get_vault_address(owner.key, reward_mint.key, &anchor_spl::token::ID)
+    )]
+    pub owner_vault: AccountInfo<'info>, // Owner's associated token account, created by Token Metadata if it does not exist
+
+    pub pnft: ProgrammableNft<'info>, // Token Metadata accounts
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner can take back expired rewards
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ReclaimExpiredPnftRewardParams {
+    pub reward_index: u8, // Index of the expired programmable NFT
+}
+
+// ------------ CREATE TEMPLATE -------------------------
+#[derive(Accounts)]
+#[instruction(params: CreateTemplateParams)]
+pub struct CreateTemplate<'info> {
+    #[account(
+        init,
+        seeds = [
+            TEMPLATE_SEED,
+            master_raffle.key().as_ref(),
+            &params.index.to_le_bytes(),
+        ],
+        bump,
+        payer = owner,
+        space = get_template_size(params.clone()),
+    )]
+    pub template: Account<'info, RaffleTemplate>, // Account data to be created, PDA of the master raffle and index
+    pub keeper: AccountInfo<'info>, // Needed to sign for and own the template vaults
+
+    #[account(
+        has_one = owner, 
+    )]
+    pub master_raffle: Account<'info, MasterRaffle>, // Parent raffle every round is created under
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Owner pays for the account creation
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Needed for the account creation
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct CreateTemplateParams {
+    pub index: u64, // Used to derive the template, lets a master have more than one
+    pub name: String, // Something human readable, every round is named after it
+    pub max_tickets_per_holder: u8, // Same as CreateRaffleParams
+    pub payment_option_count: u8, // Same as CreateRaffleParams
+    pub reward_count: u8, // Same as CreateRaffleParams
+    pub max_holder_count: u16, // Same as CreateRaffleParams
+    pub duration: u64, // How long each round sells tickets for (In Seconds)
+    pub claim_window: u64, // Same as CreateRaffleParams
+    pub max_total_tickets: u32, // Same as CreateRaffleParams
+}
+
+// ------------ LOAD TEMPLATE PAYMENT OPTION -------------------------
+#[derive(Accounts)]
+pub struct LoadTemplatePaymentOption<'info> {
+    #[account(
+        mut, 
+        has_one = owner, 
+        constraint = template.owner == owner.key()
+    )]
+    pub template: Account<'info, RaffleTemplate>, // Account data
+
+    pub payment_mint: AccountInfo<'info>, // Mint every round will take as payment, checked when loaded
+
+    // Signers
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to load a payment option
+}
 
+// ------------ LOAD TEMPLATE REWARD -------------------------
+#[derive(Accounts)]
+pub struct LoadTemplateReward<'info> {
     #[account(
         mut, 
-        constraint = gatekeeper.key == &payment_vault.owner 
-        && get_associated_token_address(&gatekeeper.key(), &payment_vault.mint) == payment_vault.key()
+        has_one = owner, 
+        constraint = template.owner == owner.key()
     )]
-    pub payment_vault: Account<'info, TokenAccount>, // Vault owned by the gatekeeper to store the token
+    pub template: Account<'info, RaffleTemplate>, // Account data
 
     #[account(
-        mut, 
-        constraint = holder.key == &holder_vault.owner 
-        && get_associated_token_address(&holder.key(), &holder_vault.mint) == holder_vault.key()
-        && holder_vault.mint == payment_vault.mint
+        seeds = [template.to_account_info().key.as_ref()],
+        bump = template.nonce,
     )]
-    pub holder_vault: Account<'info, TokenAccount>, // Holds the token to buy the ticket
+    keeper: AccountInfo<'info>, // Used as a check
 
-    // Signers
-    #[account(mut)]
-    pub holder: Signer<'info>, // Person buying the tickets
-    #[account(mut)]
-    pub owner: AccountInfo<'info>, // Used as a check    
-    pub token_program: AccountInfo<'info>, // Used to TX
-}
-#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
-pub struct BuyTicketsParam {
-    pub payment_option_index: u8, // Index of the payment option used
-}
+    pub reward_mint: AccountInfo<'info>, // Mint of the reward, checked when loaded
+    #[account(
+        constraint = is_associated_vault(&template_vault, keeper.key, reward_mint.key)
+    )]
+    pub template_vault: AccountInfo<'info>, // SPL vault owned by the keeper, funds every round
 
-// ------------ RNG CB ---------------------------
-#[derive(Accounts)]
-pub struct RngCallback<'info> {
+    // Signers
     #[account(mut)]
-    pub state: AccountLoader<'info, RngBot>, // The account to transfer the new RNG to
-    pub vrf_account: AccountInfo<'info>, // Account with new RNG from Switchboard
+    pub owner: Signer<'info>, // Only the owner should be able to load a reward
 }
 
-// ------------ PICK WINNER ---------------------------
+// ------------ UPDATE TEMPLATE -------------------------
 #[derive(Accounts)]
-pub struct PickWinner<'info> {
+pub struct UpdateTemplate<'info> {
     #[account(
         mut, 
         has_one = owner, 
-        constraint = raffle.owner == owner.key()
+        constraint = template.owner == owner.key()
     )]
-    pub raffle: Account<'info, Raffle>, // Account data
+    pub template: Account<'info, RaffleTemplate>, // Account data
+
+    #[account(mut)]
+    pub owner: Signer<'info>, // Only the owner should be able to change the template
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateTemplateParams {
+    pub active: bool, // False stops new rounds from being spawned
+    pub duration: u64, // How long each round sells tickets for (In Seconds)
+}
 
+// ------------ UPDATE TEMPLATE PAYEES -------------------------
+#[derive(Accounts)]
+pub struct UpdateTemplatePayees<'info> {
     #[account(
         mut, 
-        constraint = rng_bot.key() == raffle.rng_bot,
+        has_one = owner, 
+        constraint = template.owner == owner.key()
     )]
-    pub rng_bot: AccountLoader<'info, RngBot>, // Holdes the RNG value
+    pub template: Account<'info, RaffleTemplate>, // Account data
 
     #[account(mut)]
-    pub owner: AccountInfo<'info>, // No need for this to be signed, anyone could call it
+    pub owner: Signer<'info>, // Only the owner should be able to split the proceeds
+}
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateTemplatePayeesParams {
+    pub payees: Vec<Payee>, // Same as UpdatePayeesParams, copied into every round
+    pub dust_recipient: Pubkey, // Same as UpdatePayeesParams
 }
 
-// ------------ CASH OUT OWNER ---------------------------
+// ------------ WITHDRAW TEMPLATE FUNDS -------------------------
 #[derive(Accounts)]
-pub struct CashOutOwner<'info> {
+pub struct WithdrawTemplateFunds<'info> {
     #[account(
-        mut, 
         has_one = owner, 
-        constraint = raffle.owner == owner.key()
+        constraint = template.owner == owner.key()
     )]
-    pub raffle: Account<'info, Raffle>, // Account data
+    pub template: Account<'info, RaffleTemplate>, // Account data
 
     #[account(
-        seeds = [raffle.to_account_info().key.as_ref()],
-        bump = raffle.nonce,
+        seeds = [template.to_account_info().key.as_ref()],
+        bump = template.nonce,
     )]
-    gatekeeper: AccountInfo<'info>, // Owner of the payment vault needed to sign the TX
+    keeper: AccountInfo<'info>, // Needs to sign the TX
 
+    pub mint: AccountInfo<'info>, // Mint of the template vault
     #[account(
         mut, 
-        constraint = gatekeeper.key == &payment_vault.owner 
-        && get_associated_token_address(&gatekeeper.key(), &payment_vault.mint) == payment_vault.key()
+        constraint = is_associated_vault(&template_vault, keeper.key, mint.key)
     )]
-    pub payment_vault: Account<'info, TokenAccount>, // SPL vault users paid into, owned by the gatekeeper
+    pub template_vault: AccountInfo<'info>, // SPL vault owned by the keeper
 
     #[account(
         mut, 
-        constraint = owner.key == &owner_vault.owner 
-        && owner_vault.mint == payment_vault.mint
-        && get_associated_token_address(&owner.key(), &payment_vault.mint) == owner_vault.key()
+        constraint = is_associated_vault(&owner_vault, owner.key, mint.key)
     )]
-    pub owner_vault: Account<'info, TokenAccount>, // SPL vault owned by owner
+    pub owner_vault: AccountInfo<'info>, // SPL vault owned by owner
 
     // Signers
     #[account(mut)]
-    pub owner: Signer<'info>, // Needs to sign
+    pub owner: Signer<'info>, // Only the owner should be able to take funds back
+    #[account(constraint = is_token_program(token_program.key))]
     pub token_program: AccountInfo<'info>, // Used to TX
 }
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct WithdrawTemplateFundsParams {
+    pub amount: u64, // How much to take out of the template vault
+}
 
-// ------------ REDEEM REWARD -------------------------
+// ------------ SPAWN FROM TEMPLATE -------------------------
 #[derive(Accounts)]
-pub struct RedeemReward<'info> {
+pub struct SpawnFromTemplate<'info> {
     #[account(
         mut, 
-        has_one = owner, 
-        constraint = raffle.owner == owner.key()
+        has_one = master_raffle,
     )]
-    pub raffle: Account<'info, Raffle>, // Has the data
+    pub template: Account<'info, RaffleTemplate>, // Rules for the next round
 
     #[account(
-        seeds = [raffle.to_account_info().key.as_ref()],
-        bump = raffle.nonce,
+        seeds = [template.to_account_info().key.as_ref()],
+        bump = template.nonce,
     )]
-    gatekeeper: AccountInfo<'info>, // Needs to sign the TX
+    keeper: AccountInfo<'info>, // Needs to sign for the template vaults
 
     #[account(
-        mut, 
-        constraint = gatekeeper.key == &reward_vault.owner 
-        && get_associated_token_address(&gatekeeper.key(), &reward_vault.mint) == reward_vault.key()
+        init,
+        seeds = [
+            RAFFLE_SEED,
+            master_raffle.key().as_ref(),
+            &master_raffle.raffle_counter.to_le_bytes(),
+        ],
+        bump,
+        payer = payer,
+        space = get_raffle_size(template.get_create_raffle_params()),
     )]
-    pub reward_vault: Account<'info, TokenAccount>, // SPL vault owned by the gatekeeper
+    pub raffle: Account<'info, Raffle>, // Next round to be created, PDA of the master raffle and its raffle counter
+    pub gatekeeper: AccountInfo<'info>, // Needed to sign for and own all of the SPL vaults
 
+    #[account(mut)]
+    pub master_raffle: Account<'info, MasterRaffle>, // Parent raffle that indexes the new round
     #[account(
-        mut, 
-        constraint = winner.key == &winner_vault.owner 
-        && winner_vault.mint == reward_vault.mint
-        && get_associated_token_address(&winner.key(), &winner_vault.mint) == winner_vault.key()
+        constraint = rng_bot.key() == master_raffle.rng_bot
     )]
-    pub winner_vault: Account<'info, TokenAccount>, // SPL vault owned by the winner
+    pub rng_bot: AccountLoader<'info, RngBot>, // Must check RNG bot
 
     // Signers
     #[account(mut)]
-    pub winner: Signer<'info>, // Needs to sign to redeem
-    pub owner: AccountInfo<'info>, // Used for a check
-    pub token_program: AccountInfo<'info>, // Used to TX
+    pub payer: Signer<'info>, // Anyone can spawn the next round, they pay for the account creation
+    #[account(constraint = is_token_program(token_program.key))]
+    pub token_program: AccountInfo<'info>, // Used to TX, checked since no owner signs
+    #[account(address = solana_program::system_program::ID)]
+    pub system_program: AccountInfo<'info>, // Needed for the account creation
 }
 
 // ------------ STRUCTS -------------------------------
@@ -893,6 +4007,7 @@ pub struct MasterRaffle {
     pub name: String, //Something human readable
     pub master_raffle: Pubkey, //Self Pointer, easier to call on the frontend
     pub owner: Pubkey, //Owner of the raffle who has the authority to call all functions
+    pub index: u64, //Seed used with the owner to derive this account
 
     // RNG
     pub oracle: Pubkey, //The thing that cranks out our RNG
@@ -901,7 +4016,23 @@ pub struct MasterRaffle {
 
     // Indexs
     pub max_raffles: u16, //How many raffles this can index
+    pub raffle_counter: u64, //How many raffles have ever been created, used as the seed for the next raffle
     pub raffles: Vec<Pubkey>, //Index of raffles
+
+    // Emergency
+    pub paused: bool, //When set, no child raffle can sell tickets
+
+    // Operator
+    pub operator: Pubkey, //Besides the owner, who can grant free tickets, 0'd if there is none
+    pub voucher_authority: Pubkey, //Signs vouchers for free tickets, 0'd if there is none
+
+    // Jackpot
+    pub jackpot_keeper: Pubkey, //PDA that owns the jackpot vault, 0'd until the jackpot is configured
+    pub jackpot_nonce: u8, //PDA nonce
+    pub jackpot_mint: Pubkey, //Mint the jackpot is pooled in
+    pub jackpot_bps: u16, //Slice of ticket sales new raffles send to the jackpot
+    pub jackpot_odds_bps: u16, //Chance a jackpot reward is won
+    pub jackpot_balance: u64, //What is in the jackpot and not yet won
 }
 
 #[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize)]
@@ -910,8 +4041,40 @@ pub struct TicketPaymentOption {
     pub payment_mint: Pubkey, //The mint of the SPL used to purchase 'tickets_per_payment' tickets
     pub payment_amount: u64, //The amount of the SPL token needed to purchase 'tickets_per_payment' tickets
     pub tickets_per_payment: u8, //The amount of tickets given to the purchaser
+    pub weight_bps: u16, //How much each ticket bought with this option counts in the draw (10000 is 1x)
+    pub end_payment_amount: u64, //Price at the end date when ramping from payment_amount, 0 if the price is fixed
+    pub price_steps: Vec<PriceStep>, //Prices that kick in at set times, payment_amount applies before the first one
+    pub max_purchases: u64, //How many times this payment option can be executed, 0 if there is no limit
     pub payment_tally: u64, //How many times this payment option was executed
-    pub cashed_out: bool, //Marked when the vault is cleared
+    pub amount_paid: u64, //Everything paid with this option, prices can change so this is what gets cashed out
+    pub cashed_out: bool, //Marked when everything owed to the payees for the mint has been withdrawn
+}
+
+#[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct Payee {
+    pub recipient: Pubkey, //Wallet that gets paid, into its associated token account
+    pub share_bps: u16, //Share of the proceeds (10000 is all of it)
+}
+
+#[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct MintWithdrawal {
+    pub mint: Pubkey, //Payment mint the proceeds were paid in
+    pub amount: u64, //Everything the payees and the jackpot took out of the mint's vault
+}
+
+#[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct PriceStep {
+    pub starts_at: u64, //When this price kicks in (In Unix Time)
+    pub payment_amount: u64, //The price for 'tickets_per_payment' tickets from then on
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub enum RewardKind {
+    Spl, //Held in a gatekeeper SPL vault, reward_amount is in the mint's units
+    Lamports, //Held by the gatekeeper itself, reward_amount is in lamports
+    PotShare, //A share of the ticket sales in reward_mint, reward_amount is set when it is redeemed
+    Jackpot, //The master's jackpot, reward_amount is set if it is won
+    ProgrammableNft, //Held frozen in a gatekeeper SPL vault and only moved through Token Metadata, reward_amount is 1
 }
 
 #[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize)]
@@ -923,12 +4086,20 @@ pub struct Reward {
 
     pub winner: Pubkey, //The pubkey of the winner, once this is set, they can redeem what's in the vault * reward_amount
     pub reward_redeemed: bool, //When the winner redeems their prize, this will be set
+    pub sponsor: Pubkey, //Who put up the reward, the owner unless it was added by a sponsor
+    pub kind: RewardKind, //What the reward is paid out in
+    pub pot_share_bps: u16, //For pot shares, how much of the pot is won (10000 is all of it)
+    pub picked_at: u64, //When the current winner was picked (In Unix Time)
+    pub rerolls: u8, //How many times the reward was drawn again
+    pub tier: u8, //Rank of the reward, tier 0 is the grand prize and lower tiers are always drawn first
 }
 
 #[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize)]
 pub struct TicketHolder {
     pub holder: Pubkey, //The pubkey of the holder
     pub tickets: u8, //How many tickets this holder has
+    pub entries: u32, //Weighted tickets used in the draw, each ticket adds its payment option's weight_bps
+    pub option_tickets: Vec<u8>, //Tickets bought with each payment option, the rest were granted at 1x
 }
 
 #[account]
@@ -940,30 +4111,135 @@ pub struct Raffle {
     pub owner: Pubkey, //Owner of the raffle who has the authority to call all functions
     pub gatekeeper: Pubkey, //PDA that signs for the transactions
     pub nonce: u8, //PDA nonce
+    pub index: u64, //Seed used with the master raffle to derive this account
 
     // Limits
     pub max_tickets_per_holder: u8, //Used to make the raffle a little more fair
     pub payment_option_count: u8, //Used to size the account correctly
     pub reward_count: u8, //Used to size the account correctly
     pub max_holder_count: u16, //Used to size the account correctly
+    pub max_sponsor_count: u8, //Used to size the account correctly
+    pub max_free_tickets: u32, //How many tickets can be granted without paying
+    pub free_tickets_granted: u32, //How many tickets have been granted, not counted in any payment tally
+    pub max_rerolls: u8, //How many times each unclaimed reward can be drawn again
+    pub reroll_window: u64, //How long a winner has to claim before the reward can be drawn again (In Seconds)
+    pub claim_window: u64, //How long after the end date winners have to redeem before the owner can (In Seconds)
+    pub max_total_tickets: u32, //How many tickets can be sold across every payment option, 0 if there is no limit
+    pub tickets_sold: u32, //How many tickets have been bought, free tickets are not counted
 
     // Rules
     pub start_date: u64, //When the Raffle starts (In Unix Time)
     pub end_date: u64, //When the Raffle ends (In Unix Time)
+    pub ramp_end_date: u64, //End date set at the start, price ramps keep to it when the end date moves
+    pub end_date_updates: u16, //How many times the owner has moved the end date
+    pub paused_at: u64, //When the current pause started (In Unix Time), 0 if not paused
+    pub pause_count: u16, //How many times the raffle has been paused
+    pub total_paused_duration: u64, //How long the raffle has spent paused (In Seconds)
 
     // RNG Bot
     pub rng_bot: Pubkey, //RNG bot from the master raffle that created it
 
+    // Jackpot
+    pub jackpot_bps: u16, //Slice of ticket sales sent to the master's jackpot when cashing out
+
+    // Crank
+    pub crank_fee: u64, //Lamports paid to whoever distributes a reward
+    pub crank_fee_balance: u64, //Lamports the gatekeeper holds for crank fees
+
+    // Proceeds
+    pub dust_recipient: Pubkey, //Payee that gets the rounding dust
+    pub payees: Vec<Payee>, //Who ticket sales are split between, the owner gets everything if there are none
+    pub withdrawals: Vec<MintWithdrawal>, //What has been paid out of each payment mint, added on its first withdrawal
+
     // Buying a Ticket
     pub payment_options: Vec<TicketPaymentOption>, //Ways people can purchase Tickets
 
     // Prizes
     pub rewards: Vec<Reward>, //Prizes people can redeem
+    pub sponsors: Vec<Pubkey>, //Besides the owner, who can add rewards after the raffle starts
 
     // Holders
     pub holders: Vec<TicketHolder>, //Everyone who buys a ticket
 }
 
+impl Raffle {
+    // Sized like create_raffle with the raffle's current limits, add_reward can raise reward_count
+    pub fn get_create_raffle_params(&self) -> CreateRaffleParams {
+        return CreateRaffleParams {
+            name: self.name.clone(),
+            max_tickets_per_holder: self.max_tickets_per_holder,
+            payment_option_count: self.payment_option_count,
+            reward_count: self.reward_count,
+            max_holder_count: self.max_holder_count,
+            max_sponsor_count: self.max_sponsor_count,
+            max_free_tickets: self.max_free_tickets,
+            max_rerolls: self.max_rerolls,
+            reroll_window: self.reroll_window,
+            claim_window: self.claim_window,
+            max_total_tickets: self.max_total_tickets,
+        };
+    }
+}
+
+#[account]
+pub struct RaffleTemplate {
+    // Authorities
+    pub name: String, //Something human readable, every round is named after it
+    pub template: Pubkey, //Self Pointer, easier to call on the frontend
+    pub master_raffle: Pubkey, //Parent master raffle every round is created under
+    pub owner: Pubkey, //Owner of the master raffle, owns every round
+    pub keeper: Pubkey, //PDA that owns the template vaults
+    pub nonce: u8, //PDA nonce
+    pub index: u64, //Seed used with the master raffle to derive this account
+
+    // Limits
+    pub max_tickets_per_holder: u8, //Copied into every round
+    pub payment_option_count: u8, //Used to size the account correctly
+    pub reward_count: u8, //Used to size the account correctly
+    pub max_holder_count: u16, //Copied into every round
+    pub claim_window: u64, //Copied into every round
+    pub max_total_tickets: u32, //Copied into every round
+
+    // Rules
+    pub duration: u64, //How long each round sells tickets for (In Seconds)
+    pub active: bool, //New rounds can only be spawned while this is set
+    pub last_raffle: Pubkey, //Most recent round
+    pub last_end_date: u64, //When the most recent round ends, the next one can be spawned after it (In Unix Time)
+    pub rounds: u64, //How many rounds have been spawned
+
+    // Proceeds
+    pub dust_recipient: Pubkey, //Copied into every round
+    pub payees: Vec<Payee>, //Copied into every round, the owner gets everything if there are none
+
+    // Copied into every round
+    pub payment_options: Vec<TicketPaymentOption>,
+    pub rewards: Vec<Reward>, //Paid out of the template vaults
+}
+impl RaffleTemplate {
+    // Every round is sized as if it was created with create_raffle
+    pub fn get_create_raffle_params(&self) -> CreateRaffleParams {
+        return CreateRaffleParams {
+            name: self.name.clone(),
+            max_tickets_per_holder: self.max_tickets_per_holder,
+            payment_option_count: self.payment_option_count,
+            reward_count: self.reward_count,
+            max_holder_count: self.max_holder_count,
+            max_sponsor_count: 0,
+            max_free_tickets: 0,
+            max_rerolls: 0,
+            reroll_window: 0,
+            claim_window: self.claim_window,
+            max_total_tickets: self.max_total_tickets,
+        };
+    }
+}
+
+#[account]
+pub struct VoucherBitmap {
+    pub raffle: Pubkey, //Raffle the nonces are for
+    pub used: [u8; 1024], //One bit per nonce, set once the voucher is redeemed
+}
+
 #[account(zero_copy)]
 pub struct RngBot {
     pub authority: Pubkey,
@@ -980,6 +4256,139 @@ impl Default for RngBot {
     }
 }
 
+// ------------ EVENTS -------------------------------
+#[event]
+pub struct EndDateUpdated {
+    pub raffle: Pubkey,
+    pub old_end_date: u64,
+    pub new_end_date: u64,
+    pub end_date_updates: u16,
+}
+
+#[event]
+pub struct RafflePaused {
+    pub raffle: Pubkey,
+    pub paused_at: u64,
+    pub pause_count: u16,
+}
+
+#[event]
+pub struct RaffleResumed {
+    pub raffle: Pubkey,
+    pub resumed_at: u64,
+    pub paused_duration: u64,
+    pub end_date: u64,
+}
+
+#[event]
+pub struct MasterRafflePaused {
+    pub master_raffle: Pubkey,
+    pub paused: bool,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct RewardAdded {
+    pub raffle: Pubkey,
+    pub reward_index: u8,
+    pub sponsor: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_amount: u64,
+}
+
+#[event]
+pub struct JackpotDrawn {
+    pub master_raffle: Pubkey,
+    pub raffle: Pubkey,
+    pub roll: u16,
+    pub won: bool,
+    pub jackpot_balance: u64,
+}
+
+#[event]
+pub struct RaffleSpawned {
+    pub template: Pubkey,
+    pub raffle: Pubkey,
+    pub round: u64,
+    pub start_date: u64,
+    pub end_date: u64,
+}
+
+#[event]
+pub struct TicketsGranted {
+    pub raffle: Pubkey,
+    pub holder: Pubkey,
+    pub tickets: u8,
+    pub authority: Pubkey,
+    pub free_tickets_granted: u32,
+}
+
+#[event]
+pub struct VoucherRedeemed {
+    pub raffle: Pubkey,
+    pub holder: Pubkey,
+    pub tickets: u8,
+    pub nonce: u64,
+    pub free_tickets_granted: u32,
+}
+
+#[event]
+pub struct RewardDistributed {
+    pub raffle: Pubkey,
+    pub reward_index: u8,
+    pub winner: Pubkey,
+    pub reward_amount: u64,
+    pub crank_fee: u64,
+}
+
+#[event]
+pub struct WinnerPicked {
+    pub raffle: Pubkey,
+    pub reward_index: u8,
+    pub tier: u8,
+    pub winner: Pubkey,
+    pub claim_deadline: u64,
+}
+
+#[event]
+pub struct ProceedsDistributed {
+    pub raffle: Pubkey,
+    pub payment_mint: Pubkey,
+    pub amount: u64,
+    pub payee_amounts: Vec<u64>,
+    pub jackpot_amount: u64,
+    pub collected: u64,
+    pub withdrawn: u64,
+    pub outstanding: u64,
+}
+
+#[event]
+pub struct VaultSwept {
+    pub raffle: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub reserved: u64,
+}
+
+#[event]
+pub struct RewardReclaimed {
+    pub raffle: Pubkey,
+    pub reward_index: u8,
+    pub forfeited_by: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_amount: u64,
+}
+
+#[event]
+pub struct RewardRerolled {
+    pub raffle: Pubkey,
+    pub reward_index: u8,
+    pub old_winner: Pubkey,
+    pub new_winner: Pubkey,
+    pub rerolls: u8,
+    pub claim_deadline: u64,
+}
+
 pub fn get_master_raffle_size(
     params: CreateMasterRaffleParams,
 ) -> usize {
@@ -990,16 +4399,483 @@ pub fn get_master_raffle_size(
         + (MAX_NAME_LENGTH + 1);
 }
 
-pub fn get_raffle_size(
-    params: CreateRaffleParams,
-) -> usize {
-    return 
-        8 // Program Discrimator
-        + size_of::<Raffle>()
-        + size_of::<TicketPaymentOption>() * params.payment_option_count as usize
-        + size_of::<Reward>() * params.reward_count as usize
-        + size_of::<TicketHolder>() * params.max_holder_count as usize
-        + (MAX_NAME_LENGTH + 1) * (1 + params.payment_option_count as usize + params.reward_count as usize);
+pub fn get_raffle_size(
+    params: CreateRaffleParams,
+) -> usize {
+    return 
+        8 // Program Discrimator
+        + size_of::<Raffle>()
+        + size_of::<TicketPaymentOption>() * params.payment_option_count as usize
+        + size_of::<PriceStep>() * MAX_PRICE_STEPS * params.payment_option_count as usize
+        + size_of::<Reward>() * params.reward_count as usize
+        + size_of::<TicketHolder>() * params.max_holder_count as usize
+        + params.payment_option_count as usize * params.max_holder_count as usize
+        + size_of::<Pubkey>() * params.max_sponsor_count as usize
+        + size_of::<Payee>() * MAX_PAYEES
+        + size_of::<MintWithdrawal>() * params.payment_option_count as usize
+        + (MAX_NAME_LENGTH + 1) * (1 + params.payment_option_count as usize + params.reward_count as usize);
+}
+
+pub fn get_template_size(
+    params: CreateTemplateParams,
+) -> usize {
+    return 
+        8 // Program Discrimator
+        + size_of::<RaffleTemplate>()
+        + size_of::<TicketPaymentOption>() * params.payment_option_count as usize
+        + size_of::<Reward>() * params.reward_count as usize
+        + size_of::<Payee>() * MAX_PAYEES
+        + (MAX_NAME_LENGTH + 1) * (1 + params.payment_option_count as usize + params.reward_count as usize);
+}
+
+// Sets up a new raffle and indexes it in the master, the raffle still needs payment options and rewards
+pub fn init_raffle<'info>(
+    raffle: &mut Account<'info, Raffle>,
+    master_raffle: &mut Account<'info, MasterRaffle>,
+    owner: Pubkey,
+    gatekeeper: Pubkey,
+    nonce: u8,
+    params: &CreateRaffleParams,
+) {
+
+    // Authorities
+    raffle.name = params.name.clone();
+    raffle.raffle = raffle.key();
+    raffle.master_raffle = master_raffle.key();
+    raffle.owner = owner;
+    raffle.gatekeeper = gatekeeper;
+    raffle.nonce = nonce;
+    raffle.index = master_raffle.raffle_counter;
+
+    // Limits
+    raffle.max_tickets_per_holder = params.max_tickets_per_holder;
+    raffle.payment_option_count = params.payment_option_count;
+    raffle.reward_count =params.reward_count;
+    raffle.max_holder_count = params.max_holder_count;
+    raffle.max_sponsor_count = params.max_sponsor_count;
+    raffle.max_free_tickets = params.max_free_tickets;
+    raffle.max_rerolls = params.max_rerolls;
+    raffle.reroll_window = params.reroll_window;
+    raffle.claim_window = params.claim_window;
+    raffle.max_total_tickets = params.max_total_tickets;
+    raffle.free_tickets_granted = 0;
+    raffle.tickets_sold = 0;
+
+    // Rules
+    raffle.start_date = 0;
+    raffle.end_date = !0;
+    raffle.ramp_end_date = 0;
+    raffle.end_date_updates = 0;
+    raffle.paused_at = 0;
+    raffle.pause_count = 0;
+    raffle.total_paused_duration = 0;
+
+    // RNG
+    raffle.rng_bot = master_raffle.rng_bot.key();
+
+    // Jackpot, the slice is locked in when the raffle is created
+    raffle.jackpot_bps = master_raffle.jackpot_bps;
+
+    // Crank, nothing is paid until the owner funds it
+    raffle.crank_fee = 0;
+    raffle.crank_fee_balance = 0;
+
+    // Proceeds, the owner gets everything until payees are set
+    raffle.dust_recipient = owner;
+
+    // Master Raffle
+    master_raffle.raffles.push(raffle.key());
+    master_raffle.raffle_counter += 1;
+}
+
+// Steps have to be in order and every price has to cost something
+pub fn check_price_schedule(
+    params: &LoadPaymentOptionParams,
+) -> ProgramResult {
+
+    if params.price_steps.len() > MAX_PRICE_STEPS { return Err(ErrorCode::BadPriceSchedule.into()); }
+    if params.price_steps.len() > 0 && params.end_payment_amount != 0 { return Err(ErrorCode::BadPriceSchedule.into()); }
+
+    for i in 0..params.price_steps.len() {
+        if params.price_steps[i].payment_amount < 1 { return Err(ErrorCode::NeedPaymentAmount.into()); }
+        if i > 0 && params.price_steps[i].starts_at <= params.price_steps[i - 1].starts_at { return Err(ErrorCode::BadPriceSchedule.into()); }
+    }
+
+    Ok(())
+}
+
+// Same limits as buying, but counted against max_free_tickets instead of a payment tally, free tickets weigh 1x
+pub fn credit_free_tickets(
+    raffle: &mut Raffle,
+    holder: Pubkey,
+    tickets: u8,
+) -> ProgramResult {
+
+    if tickets < 1 { return Err(ErrorCode::NeedFreeTickets.into()); }
+    if holder == raffle.owner { return Err(ErrorCode::BadBuyer.into()); }
+    if tickets > raffle.max_tickets_per_holder { return Err(ErrorCode::BuyingTooMany.into()); }
+    if raffle.free_tickets_granted + tickets as u32 > raffle.max_free_tickets { return Err(ErrorCode::TooManyFreeTickets.into()); }
+
+    let mut holder_index = raffle.holders.len();
+
+    // Check for existing holder
+    for i in 0..raffle.holders.len() {
+        if raffle.holders[i].holder == holder {
+            holder_index = i;
+            break;
+        }
+    }
+
+    if holder_index == raffle.holders.len() { // Is not in holders
+        if raffle.holders.len() + 1 > raffle.max_holder_count as usize { return Err(ErrorCode::TooManyHolders.into()); }
+
+        raffle.holders.push(
+            TicketHolder{
+                holder: holder,
+                tickets: tickets,
+                entries: tickets as u32 * MAX_BPS as u32,
+                option_tickets: Vec::new(),
+            }
+        );
+    } else {
+        // Need to avoid overflows
+        if (raffle.holders[holder_index].tickets as u16 + tickets as u16) > raffle.max_tickets_per_holder as u16 { 
+            return Err(ErrorCode::BuyingTooMany.into()); 
+        }
+
+        raffle.holders[holder_index].tickets += tickets;
+        raffle.holders[holder_index].entries += tickets as u32 * MAX_BPS as u32;
+    }
+
+    raffle.free_tickets_granted += tickets as u32;
+
+    Ok(())
+}
+
+// Grows a raffle account, the payer covers the extra rent
+pub fn grow_raffle_account<'info>(
+    raffle: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    additional_size: usize,
+) -> ProgramResult {
+
+    let new_size = raffle.data_len() + additional_size;
+    let new_minimum_balance = Rent::get()?.minimum_balance(new_size);
+    let lamports_needed = new_minimum_balance.saturating_sub(raffle.lamports());
+
+    if lamports_needed > 0 {
+        solana_program::program::invoke(
+            &solana_program::system_instruction::transfer(payer.key, raffle.key, lamports_needed),
+            &[payer.clone(), raffle.clone(), system_program.clone()],
+        )?;
+    }
+
+    raffle.realloc(new_size, false)
+}
+
+// Pays the caller of a distribution out of what the owner set aside
+pub fn pay_crank_fee<'info>(
+    raffle: &mut Raffle,
+    gatekeeper: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    signer: &[&[&[u8]]],
+) -> std::result::Result<u64, ProgramError> {
+
+    let crank_fee = std::cmp::min(raffle.crank_fee, raffle.crank_fee_balance);
+    if crank_fee == 0 { return Ok(0); }
+
+    let fee_tx_result = solana_program::program::invoke_signed(
+        &solana_program::system_instruction::transfer(gatekeeper.key, payer.key, crank_fee),
+        &[gatekeeper.clone(), payer.clone(), system_program.clone()],
+        signer,
+    );
+
+    if !fee_tx_result.is_ok() {
+        return Err(ErrorCode::CouldNotTX.into());
+    }
+
+    raffle.crank_fee_balance -= crank_fee;
+
+    Ok(crank_fee)
+}
+
+// Classic SPL and Token-2022 both work, every vault is checked against the program that owns it
+pub fn is_token_program(
+    key: &Pubkey,
+) -> bool {
+    return *key == anchor_spl::token::ID || *key == spl_token_2022::ID;
+}
+
+// Associated token account under either token program, derived the same way the ATA program does
+pub fn get_vault_address(
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    return Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &spl_associated_token_account::ID,
+    ).0;
+}
+
+// Only the base state is read, Token-2022 extensions come after it
+pub fn get_token_account(
+    vault: &AccountInfo,
+) -> std::result::Result<spl_token_2022::state::Account, ProgramError> {
+
+    if !is_token_program(vault.owner) { return Err(ErrorCode::BadTokenProgram.into()); }
+
+    let data = vault.try_borrow_data()?;
+    let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+
+    return Ok(account.base);
+}
+
+// Has to be the wallet's associated token account for the mint, under the program that owns it
+pub fn is_associated_vault(
+    vault: &AccountInfo,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+) -> bool {
+
+    let account = match get_token_account(vault) {
+        Ok(account) => account,
+        Err(_) => return false,
+    };
+
+    return account.owner == *wallet 
+        && account.mint == *mint 
+        && get_vault_address(wallet, mint, vault.owner) == *vault.key;
+}
+
+// Mints with extensions newer than this program (non-transferable, transfer hooks, ...) or that freeze new vaults are turned away
+pub fn get_mint_decimals(
+    mint: &AccountInfo,
+) -> std::result::Result<u8, ProgramError> {
+
+    if !is_token_program(mint.owner) { return Err(ErrorCode::BadTokenProgram.into()); }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let extension_types = state.get_extension_types().map_err(|_| ErrorCode::UnsupportedMint)?;
+
+    if extension_types.contains(&ExtensionType::DefaultAccountState) {
+        let default_state = state.get_extension::<DefaultAccountState>()?;
+        if default_state.state == spl_token_2022::state::AccountState::Frozen as u8 { return Err(ErrorCode::UnsupportedMint.into()); }
+    }
+
+    return Ok(state.base.decimals);
+}
+
+// Rent exempt size of a new vault for the mint, Token-2022 vaults carry the extensions the mint requires
+pub fn get_vault_size(
+    mint: &AccountInfo,
+) -> std::result::Result<usize, ProgramError> {
+
+    if *mint.owner != spl_token_2022::ID { return Ok(<spl_token_2022::state::Account as solana_program::program_pack::Pack>::LEN); }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let extension_types = state.get_extension_types().map_err(|_| ErrorCode::UnsupportedMint)?;
+
+    // The ATA program always makes Token-2022 vaults immutable
+    let mut vault_extension_types = ExtensionType::get_required_init_account_extensions(&extension_types);
+    vault_extension_types.push(ExtensionType::ImmutableOwner);
+
+    return Ok(ExtensionType::get_account_len::<spl_token_2022::state::Account>(&vault_extension_types));
+}
+
+// transfer_checked works with both programs and is the only transfer Token-2022 allows for mints with transfer fees
+pub fn transfer_tokens<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    signer: &[&[&[u8]]],
+) -> ProgramResult {
+
+    if !is_token_program(token_program.key) || from.owner != token_program.key { return Err(ErrorCode::BadTokenProgram.into()); }
+
+    let decimals = get_mint_decimals(mint)?;
+    let tx_result = solana_program::program::invoke_signed(
+        &spl_token_2022::instruction::transfer_checked(
+            token_program.key,
+            from.key,
+            mint.key,
+            to.key,
+            authority.key,
+            &[],
+            amount,
+            decimals,
+        )?,
+        &[
+            from.clone(),
+            mint.clone(),
+            to.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        signer,
+    );
+
+    if !tx_result.is_ok() {
+        return Err(ErrorCode::CouldNotTX.into());
+    }
+
+    Ok(())
+}
+
+// Mints with a transfer fee deliver less than was sent, payments and rewards are counted by what arrived
+pub fn get_received_amount(
+    vault: &AccountInfo,
+    amount_before: u64,
+) -> std::result::Result<u64, ProgramError> {
+    return Ok(get_token_account(vault)?.amount - amount_before);
+}
+
+// Same as the ATA program's create instruction, with the token program the mint belongs to
+pub fn create_vault_instruction(
+    payer: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> solana_program::instruction::Instruction {
+    return solana_program::instruction::Instruction {
+        program_id: spl_associated_token_account::ID,
+        accounts: vec![
+            solana_program::instruction::AccountMeta::new(*payer, true),
+            solana_program::instruction::AccountMeta::new(get_vault_address(wallet, mint, token_program), false),
+            solana_program::instruction::AccountMeta::new_readonly(*wallet, false),
+            solana_program::instruction::AccountMeta::new_readonly(*mint, false),
+            solana_program::instruction::AccountMeta::new_readonly(solana_program::system_program::ID, false),
+            solana_program::instruction::AccountMeta::new_readonly(*token_program, false),
+            solana_program::instruction::AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
+        ],
+        data: vec![],
+    };
+}
+
+// Token Metadata's PDAs for a mint, the token record tracks the state of one token account
+pub fn get_metadata_address(
+    mint: &Pubkey,
+) -> Pubkey {
+    return Pubkey::find_program_address(&[b"metadata", token_metadata::ID.as_ref(), mint.as_ref()], &token_metadata::ID).0;
+}
+
+pub fn get_edition_address(
+    mint: &Pubkey,
+) -> Pubkey {
+    return Pubkey::find_program_address(&[b"metadata", token_metadata::ID.as_ref(), mint.as_ref(), b"edition"], &token_metadata::ID).0;
+}
+
+pub fn get_token_record_address(
+    mint: &Pubkey,
+    token: &Pubkey,
+) -> Pubkey {
+    return Pubkey::find_program_address(&[b"metadata", token_metadata::ID.as_ref(), mint.as_ref(), b"token_record", token.as_ref()], &token_metadata::ID).0;
+}
+
+// Token Metadata's Transfer (V1) of one programmable NFT, the token owner is the authority and optional accounts left out are Token Metadata itself
+pub fn get_pnft_transfer_instruction(
+    token: &Pubkey,
+    token_owner: &Pubkey,
+    destination: &Pubkey,
+    destination_owner: &Pubkey,
+    mint: &Pubkey,
+    payer: &Pubkey,
+    authorization_rules: &Pubkey,
+) -> solana_program::instruction::Instruction {
+
+    let authorization_rules_program = if *authorization_rules == token_metadata::ID { token_metadata::ID } else { token_auth_rules::ID };
+
+    // Transfer is instruction 49, followed by TransferArgs::V1 with the amount and no authorization data
+    let mut data = vec![49, 0];
+    data.extend_from_slice(&1u64.to_le_bytes());
+    data.push(0);
+
+    return solana_program::instruction::Instruction {
+        program_id: token_metadata::ID,
+        accounts: vec![
+            solana_program::instruction::AccountMeta::new(*token, false),
+            solana_program::instruction::AccountMeta::new_readonly(*token_owner, false),
+            solana_program::instruction::AccountMeta::new(*destination, false),
+            solana_program::instruction::AccountMeta::new_readonly(*destination_owner, false),
+            solana_program::instruction::AccountMeta::new_readonly(*mint, false),
+            solana_program::instruction::AccountMeta::new(get_metadata_address(mint), false),
+            solana_program::instruction::AccountMeta::new_readonly(get_edition_address(mint), false),
+            solana_program::instruction::AccountMeta::new(get_token_record_address(mint, token), false),
+            solana_program::instruction::AccountMeta::new(get_token_record_address(mint, destination), false),
+            solana_program::instruction::AccountMeta::new_readonly(*token_owner, true),
+            solana_program::instruction::AccountMeta::new(*payer, true),
+            solana_program::instruction::AccountMeta::new_readonly(solana_program::system_program::ID, false),
+            solana_program::instruction::AccountMeta::new_readonly(solana_program::sysvar::instructions::ID, false),
+            solana_program::instruction::AccountMeta::new_readonly(anchor_spl::token::ID, false),
+            solana_program::instruction::AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            solana_program::instruction::AccountMeta::new_readonly(authorization_rules_program, false),
+            solana_program::instruction::AccountMeta::new_readonly(*authorization_rules, false),
+        ],
+        data: data,
+    };
+}
+
+// Programmable NFTs stay frozen, only Token Metadata can thaw them, check the rule set and freeze them again on the other side
+pub fn transfer_pnft<'info>(
+    pnft: &ProgrammableNft<'info>,
+    token: &AccountInfo<'info>,
+    token_owner: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    destination_owner: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    signer: &[&[&[u8]]],
+) -> ProgramResult {
+
+    let instruction = get_pnft_transfer_instruction(
+        token.key,
+        token_owner.key,
+        destination.key,
+        destination_owner.key,
+        mint.key,
+        payer.key,
+        pnft.authorization_rules.key,
+    );
+    let account_infos = [
+        token.clone(),
+        token_owner.clone(),
+        destination.clone(),
+        destination_owner.clone(),
+        mint.clone(),
+        pnft.metadata.clone(),
+        pnft.edition.clone(),
+        pnft.owner_token_record.clone(),
+        pnft.destination_token_record.clone(),
+        token_owner.clone(),
+        payer.clone(),
+        pnft.system_program.clone(),
+        pnft.sysvar_instructions.clone(),
+        pnft.token_program.clone(),
+        pnft.associated_token_program.clone(),
+        pnft.authorization_rules_program.clone(),
+        pnft.authorization_rules.clone(),
+        pnft.token_metadata_program.clone(),
+    ];
+
+    // The PDAs all come from the mint and the vaults, anything else is the wrong account
+    for i in 0..instruction.accounts.len() {
+        if instruction.accounts[i].pubkey != *account_infos[i].key { return Err(ErrorCode::BadPnftAccounts.into()); }
+    }
+
+    let tx_result = solana_program::program::invoke_signed(&instruction, &account_infos, signer);
+
+    if !tx_result.is_ok() {
+        return Err(ErrorCode::CouldNotTX.into());
+    }
+
+    Ok(())
 }
 
 // ERROR CODES
@@ -1037,8 +4913,8 @@ pub enum ErrorCode {
     NeedReward,
     #[msg("Need at least 1 holder")]
     NeedHolders,
-    #[msg("Bad gatekeeper nonce")]
-    BadGatekeeperNonce,
+    #[msg("Bad gatekeeper account")]
+    BadGatekeeper,
     #[msg("Need non-empty RNG")]
     NeedRngFirst,
 
@@ -1103,6 +4979,502 @@ pub enum ErrorCode {
     BadWinner,
     #[msg("Winner vault's mint does not match the reward mint")]
     BadWinnerVault,
+
+    // Update End Date
+    #[msg("End date is already set to this")]
+    SameEndDate,
+    #[msg("Shortening a raffle with tickets needs at least 1 day of notice")]
+    NeedEndDateNotice,
+
+    // Start Raffle (Scheduled)
+    #[msg("Start date can not be in the past")]
+    BadStartDate,
+    #[msg("Start date can not be more than 3 years away")]
+    TooBigStartDate,
+
+    // Pause Raffle
+    #[msg("Raffle is paused")]
+    RafflePaused,
+    #[msg("Raffle is not paused")]
+    RaffleNotPaused,
+
+    // Add Reward
+    #[msg("Too many sponsors")]
+    TooManySponsors,
+    #[msg("Only the owner or a sponsor can add rewards")]
+    BadSponsor,
+
+    // Load Pot Reward
+    #[msg("Pot share needs to be more than 0")]
+    NeedPotShare,
+    #[msg("Pot shares can not add up to more than the whole pot")]
+    BadPotShare,
+
+    // Jackpot
+    #[msg("Jackpot share can not be more than 100%")]
+    BadJackpotShare,
+    #[msg("Jackpot odds can not be more than 100%")]
+    BadJackpotOdds,
+    #[msg("Bad jackpot vault")]
+    BadJackpotVault,
+    #[msg("Jackpot mint can not change until the jackpot is won")]
+    JackpotNotEmpty,
+    #[msg("Master raffle has no jackpot")]
+    NoJackpot,
+
+    // Templates
+    #[msg("Bad template keeper account")]
+    BadTemplateKeeper,
+    #[msg("Round length needs to be between 1 second and 3 years")]
+    BadTemplateDuration,
+    #[msg("Template is not active")]
+    TemplateInactive,
+    #[msg("Previous round has not ended")]
+    PreviousRoundNotEnded,
+    #[msg("Bad template vault")]
+    BadTemplateVault,
+
+    // Grant Tickets
+    #[msg("Only the owner or the operator can grant tickets")]
+    BadOperator,
+    #[msg("Granting would go over the free ticket limit")]
+    TooManyFreeTickets,
+
+    // Redeem Voucher
+    #[msg("Master raffle has no voucher authority")]
+    NoVoucherAuthority,
+    #[msg("Voucher has already been redeemed")]
+    VoucherUsed,
+    #[msg("Voucher signature does not match")]
+    BadVoucher,
+
+    // Crank Fee
+    #[msg("No crank fee to reclaim")]
+    NoCrankFee,
+
+    // Reroll Reward
+    #[msg("Bad reward index")]
+    BadRewardIndex,
+    #[msg("Reward can not be drawn again")]
+    CannotReroll,
+    #[msg("Reward has been drawn again too many times")]
+    TooManyRerolls,
+    #[msg("Winner still has time to claim")]
+    RerollWindowOpen,
+
+    // Claim Window
+    #[msg("Claim window needs to be between 1 day and 1 year")]
+    BadClaimWindow,
+
+    // Reclaim Expired Reward
+    #[msg("Winner still has time to redeem")]
+    ClaimWindowOpen,
+
+    // Reward Index
+    #[msg("Only the owner can choose which reward is drawn")]
+    OwnerMustPick,
+
+    // Reward Tiers
+    #[msg("Rewards in a lower tier have to be drawn first")]
+    TierNotDrawn,
+
+    // Ticket Weights
+    #[msg("Need a ticket weight")]
+    NeedTicketWeight,
+
+    // Price Schedules
+    #[msg("Price steps need to be in order, up to 4, and can not be mixed with a ramp")]
+    BadPriceSchedule,
+
+    // Supply Caps
+    #[msg("Payment option has sold out")]
+    PaymentOptionSoldOut,
+    #[msg("Buying would go over the raffle's max total tickets")]
+    RaffleSoldOut,
+
+    // Revenue Splits
+    #[msg("Payees need to add up to 10000 bps, up to 8, and include the dust recipient")]
+    BadPayees,
+    #[msg("Payee vaults need to be the payees' associated token accounts, in order")]
+    BadPayeeVault,
+
+    // Programmable NFTs
+    #[msg("Reward vault is frozen, programmable NFTs are loaded with load_pnft_reward")]
+    FrozenRewardVault,
+
+    // Raffle Size
+    #[msg("Raffle would be bigger than the 10MB account limit, or 10KB for templates")]
+    RaffleTooBig,
+    #[msg("Raffle needs to be expanded to its full size before it starts")]
+    RaffleNotExpanded,
+
+    // Reroll Window
+    #[msg("Reroll window must be at least 1 hour and shorter than the claim window")]
+    BadRerollWindow,
+
+    // Free Tickets
+    #[msg("Free tickets need to be at least 1 per holder")]
+    NeedFreeTickets,
+
+    // Token-2022
+    #[msg("Token accounts and mints need to belong to the SPL Token or Token-2022 program")]
+    BadTokenProgram,
+    #[msg("This mint has a Token-2022 extension the raffle can't honor, like non-transferable tokens, transfer hooks or frozen new accounts")]
+    UnsupportedMint,
+    #[msg("Not enough SOL to create the winner's token account")]
+    NotEnoughForVault,
+
+    // Programmable NFTs moved through Token Metadata
+    #[msg("Programmable NFT rewards are always 1 token")]
+    BadPnftAmount,
+    #[msg("Token Metadata accounts don't match the programmable NFT's mint and vaults")]
+    BadPnftAccounts,
+}
+
+// Everything paid into the raffle with a mint
+pub fn get_collected_amount(
+    payment_options: &Vec<TicketPaymentOption>,
+    mint: &Pubkey,
+) -> u64 {
+
+    let mut amount = 0 as u64;
+    for i in 0..payment_options.len() {
+        if payment_options[i].payment_mint == *mint {
+            amount += payment_options[i].amount_paid;
+        }
+    }
+
+    return amount;
+}
+
+// The price at current_time, the last step that has started or a point on the ramp
+pub fn get_payment_amount(
+    payment_option: &TicketPaymentOption,
+    start_date: u64,
+    end_date: u64,
+    current_time: u64,
+) -> u64 {
+
+    let mut payment_amount = payment_option.payment_amount;
+
+    if payment_option.price_steps.len() > 0 {
+        for i in 0..payment_option.price_steps.len() {
+            if current_time >= payment_option.price_steps[i].starts_at {
+                payment_amount = payment_option.price_steps[i].payment_amount;
+            }
+        }
+    } else if payment_option.end_payment_amount != 0 && end_date > start_date {
+        let elapsed = current_time.max(start_date).min(end_date) - start_date;
+        let start = payment_option.payment_amount as i128;
+        let end = payment_option.end_payment_amount as i128;
+
+        payment_amount = (start + (end - start) * elapsed as i128 / (end_date - start_date) as i128) as u64;
+    }
+
+    return payment_amount;
+}
+
+// Everything already paid out of a mint's payment vault to the owner and the jackpot
+pub fn get_withdrawn_amount(
+    withdrawals: &Vec<MintWithdrawal>,
+    mint: &Pubkey,
+) -> u64 {
+
+    for i in 0..withdrawals.len() {
+        if withdrawals[i].mint == *mint {
+            return withdrawals[i].amount;
+        }
+    }
+
+    return 0;
+}
+
+// Ticket sales in the mint less the pot shares paid out of them, what the payees and the jackpot split
+pub fn get_owed_amount(
+    raffle: &Raffle,
+    mint: &Pubkey,
+) -> u64 {
+
+    let collected = get_collected_amount(&raffle.payment_options, mint);
+    let mut owed = collected;
+    for i in 0..raffle.rewards.len() {
+        if raffle.rewards[i].kind == RewardKind::PotShare && raffle.rewards[i].reward_mint == *mint {
+            owed -= get_pot_share_amount(collected, raffle.rewards[i].pot_share_bps);
+        }
+    }
+
+    return owed;
+}
+
+// One total per mint, every option with the mint is marked as cashed out once nothing is left
+pub fn record_withdrawal(
+    raffle: &mut Raffle,
+    mint: &Pubkey,
+    withdrawn: u64,
+    owed: u64,
+) {
+
+    let mut withdrawal_index = raffle.withdrawals.len();
+    for i in 0..raffle.withdrawals.len() {
+        if raffle.withdrawals[i].mint == *mint { withdrawal_index = i; }
+    }
+    if withdrawal_index == raffle.withdrawals.len() {
+        raffle.withdrawals.push(MintWithdrawal{ mint: *mint, amount: 0 });
+    }
+    raffle.withdrawals[withdrawal_index].amount = withdrawn;
+
+    for i in 0..raffle.payment_options.len() {
+        if raffle.payment_options[i].payment_mint == *mint {
+            raffle.payment_options[i].cashed_out = withdrawn == owed;
+        }
+    }
+}
+
+// What has to stay in the gatekeeper's vault for a mint: unpaid proceeds, unredeemed pot shares and unredeemed rewards
+pub fn get_reserved_amount(
+    raffle: &Raffle,
+    mint: &Pubkey,
+) -> u64 {
+
+    let collected = get_collected_amount(&raffle.payment_options, mint);
+    let mut reserved = collected - get_withdrawn_amount(&raffle.withdrawals, mint);
+
+    for i in 0..raffle.rewards.len() {
+        if raffle.rewards[i].reward_mint != *mint { continue; }
+        if raffle.rewards[i].kind == RewardKind::PotShare {
+            // Already counted as collected, only the redeemed ones have left the vault
+            if raffle.rewards[i].reward_redeemed {
+                reserved -= raffle.rewards[i].reward_amount;
+            }
+        } else if (raffle.rewards[i].kind == RewardKind::Spl || raffle.rewards[i].kind == RewardKind::ProgrammableNft) && !raffle.rewards[i].reward_redeemed {
+            reserved += raffle.rewards[i].reward_amount;
+        }
+    }
+
+    return reserved;
+}
+
+// Up to 8 payees adding up to 10000 bps, and the dust has to go to one of them
+pub fn check_payees(
+    payees: &Vec<Payee>,
+    dust_recipient: &Pubkey,
+) -> ProgramResult {
+
+    if payees.len() > MAX_PAYEES { return Err(ErrorCode::BadPayees.into()); }
+
+    let mut share_bps = 0 as u32;
+    let mut has_dust_recipient = false;
+    for i in 0..payees.len() {
+        if payees[i].share_bps < 1 { return Err(ErrorCode::BadPayees.into()); }
+        share_bps += payees[i].share_bps as u32;
+        if payees[i].recipient == *dust_recipient { has_dust_recipient = true; }
+    }
+
+    // No payees pays everything to the owner
+    if payees.len() > 0 && (share_bps != MAX_BPS as u32 || !has_dust_recipient) { return Err(ErrorCode::BadPayees.into()); }
+
+    Ok(())
+}
+
+// Each payee's share of amount in order, the dust recipient also gets what is left after rounding down
+pub fn get_payee_amounts(
+    payees: &Vec<Payee>,
+    dust_recipient: &Pubkey,
+    amount: u64,
+) -> Vec<u64> {
+
+    let mut payee_amounts: Vec<u64> = Vec::with_capacity(payees.len());
+    let mut dust = amount;
+    for i in 0..payees.len() {
+        let payee_amount = get_pot_share_amount(amount, payees[i].share_bps);
+        payee_amounts.push(payee_amount);
+        dust -= payee_amount;
+    }
+
+    for i in 0..payees.len() {
+        if payees[i].recipient == *dust_recipient {
+            payee_amounts[i] += dust;
+            break;
+        }
+    }
+
+    return payee_amounts;
+}
+
+// Rounds down, the owner keeps the dust
+pub fn get_pot_share_amount(
+    collected: u64,
+    pot_share_bps: u16,
+) -> u64 {
+    return (collected as u128 * pot_share_bps as u128 / MAX_BPS as u128) as u64;
+}
+
+// Pot shares are only known once ticket sales are over, they come out of the payment vault
+pub fn get_reward_amount(
+    raffle: &Raffle,
+    index: usize,
+) -> u64 {
+
+    let reward = &raffle.rewards[index];
+    if reward.kind == RewardKind::PotShare {
+        return get_pot_share_amount(get_collected_amount(&raffle.payment_options, &reward.reward_mint), reward.pot_share_bps);
+    }
+
+    return reward.reward_amount;
+}
+
+// Uses the same RNG as the draw, mixed with the raffle so every raffle gets its own roll
+pub fn get_jackpot_roll(
+    rng_buffer: &[u8; 32],
+    raffle: &Pubkey,
+) -> u16 {
+
+    let hash = solana_program::hash::hashv(&[rng_buffer, raffle.as_ref(), JACKPOT_SEED]).to_bytes();
+    let mut roll_bytes = [0u8; 8];
+    roll_bytes.copy_from_slice(&hash[0..8]);
+
+    return (u64::from_le_bytes(roll_bytes) % MAX_BPS as u64) as u16;
+}
+
+// What the voucher authority signs: "holder gets tickets in raffle, nonce"
+pub fn get_voucher_message(
+    raffle: &Pubkey,
+    holder: &Pubkey,
+    tickets: u8,
+    nonce: u64,
+) -> Vec<u8> {
+
+    let mut message = Vec::with_capacity(VOUCHER_SEED.len() + 32 + 32 + 1 + 8);
+    message.extend_from_slice(VOUCHER_SEED);
+    message.extend_from_slice(raffle.as_ref());
+    message.extend_from_slice(holder.as_ref());
+    message.push(tickets);
+    message.extend_from_slice(&nonce.to_le_bytes());
+
+    return message;
+}
+
+// Reads a single signature ed25519 instruction, everything has to be inside the instruction itself
+pub fn get_ed25519_signer_and_message(
+    data: &[u8],
+) -> Option<(Pubkey, Vec<u8>)> {
+
+    // 1 byte signature count, 1 byte padding, then 7 u16 offsets
+    if data.len() < 16 || data[0] != 1 { return None; }
+
+    let read_u16 = |at: usize| (data[at] as u16 | (data[at + 1] as u16) << 8) as usize;
+    let signature_instruction_index = read_u16(4);
+    let public_key_offset = read_u16(6);
+    let public_key_instruction_index = read_u16(8);
+    let message_data_offset = read_u16(10);
+    let message_data_size = read_u16(12);
+    let message_instruction_index = read_u16(14);
+
+    if signature_instruction_index != u16::MAX as usize
+        || public_key_instruction_index != u16::MAX as usize
+        || message_instruction_index != u16::MAX as usize { return None; }
+    if data.len() < public_key_offset + 32 || data.len() < message_data_offset + message_data_size { return None; }
+
+    let signer = Pubkey::new(&data[public_key_offset..public_key_offset + 32]);
+    let message = data[message_data_offset..message_data_offset + message_data_size].to_vec();
+
+    return Some((signer, message));
+}
+
+// Returns rewards.len() if there is nothing left for the winner to redeem
+pub fn get_redeemable_reward_index(
+    rewards: &Vec<Reward>,
+    winner: &Pubkey,
+    kinds: &[RewardKind],
+) -> usize {
+
+    for i in 0..rewards.len() {
+        if !kinds.contains(&rewards[i].kind) { continue; }
+        if rewards[i].winner == *winner && !rewards[i].reward_redeemed {
+            return i;
+        }
+    }
+
+    return rewards.len();
+}
+
+// Lowest tier first like the draw, returns rewards.len() if nothing can be rerolled yet
+pub fn get_rerollable_reward_index(
+    raffle: &Raffle,
+    current_time: u64,
+) -> usize {
+
+    let rewards = &raffle.rewards;
+    let null_winner = Pubkey::new_from_array(NULL_KEY_ARRAY).clone();
+    let mut reward_index = rewards.len();
+    for i in 0..rewards.len() {
+        if rewards[i].kind == RewardKind::Jackpot || rewards[i].reward_redeemed { continue; }
+        if rewards[i].winner == null_winner || rewards[i].winner == raffle.owner { continue; }
+        if rewards[i].rerolls >= raffle.max_rerolls || current_time <= rewards[i].picked_at + raffle.reroll_window { continue; }
+        if reward_index == rewards.len() || rewards[i].tier < rewards[reward_index].tier {
+            reward_index = i;
+        }
+    }
+
+    return reward_index;
+}
+
+// Winners get the full claim window from when they were picked, so a reroll doesn't cut the new winner short
+pub fn get_claim_deadline(
+    raffle: &Raffle,
+    index: usize,
+) -> u64 {
+    return std::cmp::max(raffle.end_date, raffle.rewards[index].picked_at) + raffle.claim_window;
+}
+
+// Lowest tier first, then in the order the rewards were loaded, returns rewards.len() if all are drawn
+pub fn get_next_reward_index(
+    rewards: &Vec<Reward>,
+) -> usize {
+
+    let null_winner = Pubkey::new_from_array(NULL_KEY_ARRAY).clone();
+    let mut reward_index = rewards.len();
+    for i in 0..rewards.len() {
+        if rewards[i].winner != null_winner { continue; }
+        if reward_index == rewards.len() || rewards[i].tier < rewards[reward_index].tier {
+            reward_index = i;
+        }
+    }
+
+    return reward_index;
+}
+
+// Granted tickets come first in a holder's entries, then the tickets of each payment option in order
+pub fn remove_winning_ticket(
+    holder: &mut TicketHolder,
+    ticket_roll: u64,
+    payment_options: &Vec<TicketPaymentOption>,
+) {
+
+    let mut paid_tickets = 0 as u8;
+    for i in 0..holder.option_tickets.len() {
+        paid_tickets += holder.option_tickets[i];
+    }
+
+    let mut roll = ticket_roll;
+    let mut weight = MAX_BPS as u32;
+    let granted_entries = (holder.tickets - paid_tickets) as u64 * MAX_BPS as u64;
+    if roll >= granted_entries {
+        roll -= granted_entries;
+        for i in 0..holder.option_tickets.len() {
+            let option_entries = holder.option_tickets[i] as u64 * payment_options[i].weight_bps as u64;
+
+            if roll < option_entries {
+                holder.option_tickets[i] -= 1;
+                weight = payment_options[i].weight_bps as u32;
+                break;
+            }
+            roll -= option_entries;
+        }
+    }
+
+    holder.entries -= weight;
+    holder.tickets -= 1;
 }
 
 pub fn get_winner_index(
@@ -1111,36 +5483,74 @@ pub fn get_winner_index(
     holders: &Vec<TicketHolder>,
 ) -> usize {
 
-    let mut ticket_count = 0 as u32;
+    let mut entry_count = 0 as u64;
     for i in 0..holders.len() {
-        ticket_count += holders[i].tickets as u32;
+        entry_count += holders[i].entries as u64;
     }
 
-    if ticket_count == 0 { return holders.len(); }
+    if entry_count == 0 { return holders.len(); }
     if rng_uses_left == 0 { return !0; }
 
-    let mut rng = 
-        ((rng_buffer[(((rng_uses_left - 1) * 4) + 0) as usize] as u32) << 0)  | 
-        ((rng_buffer[(((rng_uses_left - 1) * 4) + 1) as usize] as u32) << 8)  |
-        ((rng_buffer[(((rng_uses_left - 1) * 4) + 2) as usize] as u32) << 16) |
-        ((rng_buffer[(((rng_uses_left - 1) * 4) + 3) as usize] as u32) << 24);
+    return get_rolled_holder_index(get_winner_roll(rng_uses_left, rng_buffer) % entry_count, holders);
+}
+
+// Where the roll landed inside the winner's own entries, used to find the ticket that won
+pub fn get_winner_ticket_roll(
+    rng_uses_left: u8,
+    rng_buffer: &[u8; 32],
+    holders: &Vec<TicketHolder>,
+) -> u64 {
+
+    let mut entry_count = 0 as u64;
+    for i in 0..holders.len() {
+        entry_count += holders[i].entries as u64;
+    }
 
-    rng = rng % ticket_count;
+    if entry_count == 0 { return 0; }
 
-    let mut winner_index = holders.len();
+    let mut roll = get_winner_roll(rng_uses_left, rng_buffer) % entry_count;
     for i in 0..holders.len() {
-        if holders[i].tickets == 0 { continue; }
-        let holder_tickets = holders[i].tickets as u32;
+        let holder_entries = holders[i].entries as u64;
 
-        if holder_tickets >= rng {
-            winner_index = i;
+        if roll < holder_entries {
             break;
-        } else {
-            rng -= holder_tickets;
         }
+        roll -= holder_entries;
+    }
+
+    return roll;
+}
+
+// Every use hashes the whole buffer with its index, 64 bit rolls keep the modulo bias negligible for any entry count
+pub fn get_winner_roll(
+    rng_uses_left: u8,
+    rng_buffer: &[u8; 32],
+) -> u64 {
+
+    let hash = solana_program::hash::hashv(&[rng_buffer, &[rng_uses_left]]).to_bytes();
+    let mut roll_bytes = [0u8; 8];
+    roll_bytes.copy_from_slice(&hash[0..8]);
+
+    return u64::from_le_bytes(roll_bytes);
+}
+
+// Each holder owns as many roll values as they have entries, the roll has to be below the entry count
+pub fn get_rolled_holder_index(
+    roll: u64,
+    holders: &Vec<TicketHolder>,
+) -> usize {
+
+    let mut roll = roll;
+    for i in 0..holders.len() {
+        let holder_entries = holders[i].entries as u64;
+
+        if roll < holder_entries {
+            return i;
+        }
+        roll -= holder_entries;
     }
 
-    return winner_index;
+    return holders.len();
 }
 
 // #[test]
@@ -1155,6 +5565,8 @@ fn get_test_random_winner() {
             TicketHolder {
                 holder: Pubkey::new_unique(),
                 tickets: 1,
+                entries: MAX_BPS as u32,
+                option_tickets: Vec::new(),
             }
         );
     }
@@ -1163,6 +5575,8 @@ fn get_test_random_winner() {
         TicketHolder {
             holder: Pubkey::new_unique(),
             tickets: 5,
+            entries: 5 * MAX_BPS as u32,
+            option_tickets: Vec::new(),
         }
     );
 
@@ -1178,7 +5592,8 @@ fn get_test_random_winner() {
             &holders,
         );
         if index != holders.len() && index != !0 {
-            holders[index].tickets -= 1;
+            let ticket_roll = get_winner_ticket_roll(rng_uses_left, &rng_buffer, &holders);
+            remove_winning_ticket(&mut holders[index], ticket_roll, &Vec::new());
         }
         if rng_uses_left > 0 {
             rng_uses_left -= 1;
@@ -1187,5 +5602,634 @@ fn get_test_random_winner() {
     }
 }
 
+#[test]
+fn get_test_next_reward_index() {
+
+    let mut rewards: Vec<Reward> = Vec::new();
+    for tier in [2, 0, 1, 0] {
+        rewards.push(
+            Reward {
+                name: String::from("Test"),
+                reward_mint: Pubkey::new_unique(),
+                group_id: Pubkey::new_from_array(NULL_KEY_ARRAY),
+                reward_amount: 1,
+                winner: Pubkey::new_from_array(NULL_KEY_ARRAY),
+                reward_redeemed: false,
+                sponsor: Pubkey::new_unique(),
+                kind: RewardKind::Spl,
+                pot_share_bps: 0,
+                picked_at: 0,
+                rerolls: 0,
+                tier: tier,
+            }
+        );
+    }
+
+    // Grand prizes in load order, then the runner-up tiers
+    for expected in [1, 3, 2, 0] {
+        let index = get_next_reward_index(&rewards);
+        assert_eq!(index, expected);
+        rewards[index].winner = Pubkey::new_unique();
+    }
+    assert_eq!(get_next_reward_index(&rewards), rewards.len());
+}
+
+#[test]
+fn get_test_distributable_reward_index() {
+
+    let winner = Pubkey::new_unique();
+    let mut rewards: Vec<Reward> = Vec::new();
+    for kind in [RewardKind::Spl, RewardKind::Lamports, RewardKind::Jackpot, RewardKind::Lamports] {
+        rewards.push(
+            Reward {
+                name: String::from("Test"),
+                reward_mint: Pubkey::new_unique(),
+                group_id: Pubkey::new_from_array(NULL_KEY_ARRAY),
+                reward_amount: 1,
+                winner: winner,
+                reward_redeemed: false,
+                sponsor: Pubkey::new_unique(),
+                kind: kind,
+                pot_share_bps: 0,
+                picked_at: 0,
+                rerolls: 0,
+                tier: 0,
+            }
+        );
+    }
+
+    // Each distribute instruction only picks up its own kinds
+    let sol_kinds = [RewardKind::Lamports];
+    let jackpot_kinds = [RewardKind::Jackpot];
+    assert_eq!(get_redeemable_reward_index(&rewards, &winner, &sol_kinds), 1);
+    assert_eq!(get_redeemable_reward_index(&rewards, &winner, &jackpot_kinds), 2);
+    assert_eq!(get_redeemable_reward_index(&rewards, &Pubkey::new_unique(), &sol_kinds), rewards.len());
+
+    // Claimed rewards are skipped until there is nothing left
+    rewards[1].reward_redeemed = true;
+    assert_eq!(get_redeemable_reward_index(&rewards, &winner, &sol_kinds), 3);
+    rewards[3].reward_redeemed = true;
+    assert_eq!(get_redeemable_reward_index(&rewards, &winner, &sol_kinds), rewards.len());
+    rewards[2].reward_redeemed = true;
+    assert_eq!(get_redeemable_reward_index(&rewards, &winner, &jackpot_kinds), rewards.len());
+}
+
+#[test]
+fn get_test_weighted_winner() {
+
+    let payment_options = get_test_weighted_payment_options();
+
+    // 2 granted tickets at 1x and 1 ticket bought at 1.5x
+    let mut holders: Vec<TicketHolder> = vec![
+        TicketHolder {
+            holder: Pubkey::new_unique(),
+            tickets: 3,
+            entries: 2 * MAX_BPS as u32 + 15000,
+            option_tickets: vec![0, 1],
+        }
+    ];
+
+    let rng_buffer = [0u8; 32];
+    assert_eq!(get_winner_index(1, &rng_buffer, &holders), 0);
+
+    // A roll past the granted entries lands on the 1.5x ticket
+    remove_winning_ticket(&mut holders[0], 2 * MAX_BPS as u64, &payment_options);
+    assert_eq!(holders[0].tickets, 2);
+    assert_eq!(holders[0].entries, 2 * MAX_BPS as u32);
+    assert_eq!(holders[0].option_tickets, vec![0, 0]);
+
+    remove_winning_ticket(&mut holders[0], 0, &payment_options);
+    remove_winning_ticket(&mut holders[0], 0, &payment_options);
+    assert_eq!(holders[0].entries, 0);
+    assert_eq!(get_winner_index(1, &rng_buffer, &holders), holders.len());
+}
+
+#[cfg(test)]
+fn get_test_weighted_payment_options() -> Vec<TicketPaymentOption> {
+
+    return [MAX_BPS, 15000].iter().map(|weight_bps| {
+        TicketPaymentOption {
+            name: String::from("Test"),
+            payment_mint: Pubkey::new_unique(),
+            payment_amount: 100,
+            tickets_per_payment: 1,
+            weight_bps: *weight_bps,
+            end_payment_amount: 0,
+            price_steps: Vec::new(),
+            max_purchases: 0,
+            payment_tally: 0,
+            amount_paid: 0,
+            cashed_out: false,
+        }
+    }).collect();
+}
+
+#[test]
+fn get_test_winner_distribution() {
+
+    let holders: Vec<TicketHolder> = [3, 0, 5, 2].iter().map(|entries| {
+        TicketHolder {
+            holder: Pubkey::new_unique(),
+            tickets: 1,
+            entries: *entries,
+            option_tickets: Vec::new(),
+        }
+    }).collect();
+
+    // Every entry maps to exactly one roll
+    let mut hits = [0u32; 4];
+    for roll in 0..10 {
+        hits[get_rolled_holder_index(roll, &holders)] += 1;
+    }
+    assert_eq!(hits, [3, 0, 5, 2]);
+    assert_eq!(get_rolled_holder_index(10, &holders), holders.len());
+
+    // Weighted entry counts well past 32 bits still come out in proportion
+    let holders: Vec<TicketHolder> = [1, 2, 3, 4].iter().map(|weight| {
+        TicketHolder {
+            holder: Pubkey::new_unique(),
+            tickets: 255,
+            entries: weight * 1_000_000_000,
+            option_tickets: Vec::new(),
+        }
+    }).collect();
+
+    let draws = 40000;
+    let mut hits = [0u32; 4];
+    for i in 0..draws / 8 {
+        let rng_buffer = solana_program::hash::hashv(&[&(i as u64).to_le_bytes()]).to_bytes();
+        for rng_uses_left in 1..9 {
+            hits[get_winner_index(rng_uses_left, &rng_buffer, &holders)] += 1;
+        }
+    }
+    for i in 0..hits.len() {
+        let expected = draws * (i as u32 + 1) / 10;
+        assert!((hits[i] as i64 - expected as i64).abs() < 500, "holder {} won {} of {} draws, expected about {}", i, hits[i], draws, expected);
+    }
+
+    // A holder with 1 granted ticket, 2 bought at 1x and 2 bought at 1.5x
+    let payment_options = get_test_weighted_payment_options();
+    let mixed_holder = TicketHolder {
+        holder: Pubkey::new_unique(),
+        tickets: 5,
+        entries: 3 * MAX_BPS as u32 + 2 * 15000,
+        option_tickets: vec![2, 2],
+    };
+    let holders: Vec<TicketHolder> = vec![
+        TicketHolder {
+            holder: Pubkey::new_unique(),
+            tickets: 4,
+            entries: 4 * MAX_BPS as u32,
+            option_tickets: Vec::new(),
+        },
+        mixed_holder.clone(),
+    ];
+
+    // Each ticket wins as often as its weight and only its own weight is taken out
+    let draws = 24000;
+    let mut hits = [0u32; 3];
+    for i in 0..draws / 8 {
+        let rng_buffer = solana_program::hash::hashv(&[&(i as u64).to_le_bytes(), b"mixed"]).to_bytes();
+        for rng_uses_left in 1..9 {
+            if get_winner_index(rng_uses_left, &rng_buffer, &holders) != 1 { continue; }
+
+            let mut holder = mixed_holder.clone();
+            remove_winning_ticket(&mut holder, get_winner_ticket_roll(rng_uses_left, &rng_buffer, &holders), &payment_options);
+            if holder.option_tickets[1] == 1 {
+                assert_eq!(holder.entries, mixed_holder.entries - 15000);
+                hits[2] += 1;
+            } else if holder.option_tickets[0] == 1 {
+                assert_eq!(holder.entries, mixed_holder.entries - MAX_BPS as u32);
+                hits[1] += 1;
+            } else {
+                assert_eq!(holder.entries, mixed_holder.entries - MAX_BPS as u32);
+                hits[0] += 1;
+            }
+        }
+    }
+    let mixed_draws = hits[0] + hits[1] + hits[2];
+    for (i, weight) in [MAX_BPS as u32, 2 * MAX_BPS as u32, 30000].iter().enumerate() {
+        let expected = mixed_draws as u64 * *weight as u64 / mixed_holder.entries as u64;
+        assert!((hits[i] as i64 - expected as i64).abs() < 400, "ticket group {} won {} of {} draws, expected about {}", i, hits[i], mixed_draws, expected);
+    }
+
+    // Drawing every ticket empties the holder exactly
+    let mut holders = holders;
+    let mut rng_uses_left = 8;
+    let rng_buffer = solana_program::hash::hashv(&[b"drain"]).to_bytes();
+    while holders[1].tickets > 0 {
+        let index = get_winner_index(rng_uses_left, &rng_buffer, &holders);
+        let ticket_roll = get_winner_ticket_roll(rng_uses_left, &rng_buffer, &holders);
+        remove_winning_ticket(&mut holders[index], ticket_roll, &payment_options);
+        rng_uses_left = if rng_uses_left == 1 { 8 } else { rng_uses_left - 1 };
+    }
+    assert_eq!(holders[1].entries, 0);
+    assert_eq!(holders[1].option_tickets, vec![0, 0]);
+}
+
+#[test]
+fn get_test_payment_amount() {
+
+    let mut payment_option = TicketPaymentOption {
+        name: String::from("Test"),
+        payment_mint: Pubkey::new_unique(),
+        payment_amount: 100,
+        tickets_per_payment: 1,
+        weight_bps: MAX_BPS,
+        end_payment_amount: 200,
+        price_steps: Vec::new(),
+        max_purchases: 0,
+        payment_tally: 0,
+        amount_paid: 0,
+        cashed_out: false,
+    };
+
+    // Surge pricing ramps up from the start to the end date
+    assert_eq!(get_payment_amount(&payment_option, 1000, 2000, 500), 100);
+    assert_eq!(get_payment_amount(&payment_option, 1000, 2000, 1500), 150);
+    assert_eq!(get_payment_amount(&payment_option, 1000, 2000, 3000), 200);
+
+    // Pushing the end date out to 4000 would flatten the ramp, prices keep to the end date it started with
+    assert_eq!(get_payment_amount(&payment_option, 1000, 4000, 1500), 116);
+    let ramp_end_date = 2000;
+    assert_eq!(get_payment_amount(&payment_option, 1000, ramp_end_date, 1500), 150);
+    assert_eq!(get_payment_amount(&payment_option, 1000, ramp_end_date, 3000), 200);
+
+    // Early bird price until the step kicks in
+    payment_option.end_payment_amount = 0;
+    payment_option.price_steps.push(PriceStep { starts_at: 1200, payment_amount: 120 });
+    assert_eq!(get_payment_amount(&payment_option, 1000, 2000, 1100), 100);
+    assert_eq!(get_payment_amount(&payment_option, 1000, 2000, 1200), 120);
+}
+
+#[test]
+fn get_test_payee_amounts() {
+
+    let payees = vec![
+        Payee { recipient: Pubkey::new_unique(), share_bps: 6000 },
+        Payee { recipient: Pubkey::new_unique(), share_bps: 3000 },
+        Payee { recipient: Pubkey::new_unique(), share_bps: 1000 },
+    ];
+
+    // 60/30/10 of an odd amount, the dust recipient gets what rounding leaves behind
+    assert_eq!(get_payee_amounts(&payees, &payees[1].recipient, 1009), vec![605, 304, 100]);
+    assert_eq!(get_payee_amounts(&payees, &payees[0].recipient, 1000), vec![600, 300, 100]);
+}
+
+#[test]
+fn get_test_template_payees() {
+
+    let payees = vec![
+        Payee { recipient: Pubkey::new_unique(), share_bps: 7000 },
+        Payee { recipient: Pubkey::new_unique(), share_bps: 3000 },
+    ];
+
+    // Templates are checked like raffles, no payees pays the owner
+    assert!(check_payees(&payees, &payees[0].recipient).is_ok());
+    assert!(check_payees(&Vec::new(), &Pubkey::new_unique()).is_ok());
+    assert!(check_payees(&payees, &Pubkey::new_unique()).is_err());
+    assert!(check_payees(&payees[0..1].to_vec(), &payees[0].recipient).is_err());
+    assert!(check_payees(&vec![payees[0].clone(); MAX_PAYEES + 1], &payees[0].recipient).is_err());
+
+    let zero_share = vec![payees[0].clone(), payees[1].clone(), Payee { recipient: Pubkey::new_unique(), share_bps: 0 }];
+    assert!(check_payees(&zero_share, &payees[0].recipient).is_err());
+
+    // Every spawned round splits its ticket sales the same way
+    for amount in [1000, 2001] {
+        let payee_amounts = get_payee_amounts(&payees, &payees[0].recipient, amount);
+        assert_eq!(payee_amounts[1], amount * 3000 / MAX_BPS as u64);
+        assert_eq!(payee_amounts[0] + payee_amounts[1], amount);
+    }
+}
+
+#[test]
+fn get_test_pot_share_amount() {
+
+    // 50/50 of an odd pot, the owner keeps the dust
+    assert_eq!(get_pot_share_amount(1001, 5000), 500);
+    assert_eq!(get_pot_share_amount(1001, MAX_BPS), 1001);
+    assert_eq!(get_pot_share_amount(0, 5000), 0);
+
+    // Does not overflow on big pots
+    assert_eq!(get_pot_share_amount(u64::MAX, MAX_BPS), u64::MAX);
+}
+
+#[test]
+fn get_test_ed25519_signer_and_message() {
+
+    let signer = Pubkey::new_unique();
+    let message = get_voucher_message(&Pubkey::new_unique(), &Pubkey::new_unique(), 3, 42);
+
+    // Same layout the ed25519 program expects, offsets point inside the instruction
+    let public_key_offset = 16 as u16;
+    let signature_offset = public_key_offset + 32;
+    let message_data_offset = signature_offset + 64;
+
+    let mut data: Vec<u8> = vec![1, 0];
+    for offset in [signature_offset, u16::MAX, public_key_offset, u16::MAX, message_data_offset, message.len() as u16, u16::MAX] {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(signer.as_ref());
+    data.extend_from_slice(&[0u8; 64]);
+    data.extend_from_slice(&message);
+
+    assert_eq!(get_ed25519_signer_and_message(&data), Some((signer, message)));
+
+    // Signatures checked against data from another instruction are rejected
+    data[14] = 0;
+    assert_eq!(get_ed25519_signer_and_message(&data), None);
+}
+
+#[cfg(test)]
+fn get_test_raffle() -> Raffle {
+
+    return Raffle {
+        name: String::from("Test"),
+        master_raffle: Pubkey::new_unique(),
+        raffle: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        gatekeeper: Pubkey::new_unique(),
+        nonce: 255,
+        index: 0,
+        max_tickets_per_holder: 10,
+        payment_option_count: 3,
+        reward_count: 4,
+        max_holder_count: 10,
+        max_sponsor_count: 0,
+        max_free_tickets: 12,
+        free_tickets_granted: 0,
+        max_rerolls: 1,
+        reroll_window: MIN_REROLL_WINDOW,
+        claim_window: MIN_CLAIM_WINDOW,
+        max_total_tickets: 0,
+        tickets_sold: 0,
+        start_date: 1000,
+        end_date: 2000,
+        ramp_end_date: 2000,
+        end_date_updates: 0,
+        paused_at: 0,
+        pause_count: 0,
+        total_paused_duration: 0,
+        rng_bot: Pubkey::new_unique(),
+        jackpot_bps: 0,
+        crank_fee: 0,
+        crank_fee_balance: 0,
+        dust_recipient: Pubkey::new_unique(),
+        payees: Vec::new(),
+        withdrawals: Vec::new(),
+        payment_options: Vec::new(),
+        rewards: Vec::new(),
+        sponsors: Vec::new(),
+        holders: Vec::new(),
+    };
+}
+
+#[cfg(test)]
+fn get_test_reward(
+    kind: RewardKind,
+    reward_mint: Pubkey,
+    reward_amount: u64,
+    winner: Pubkey,
+) -> Reward {
+
+    return Reward {
+        name: String::from("Test"),
+        reward_mint: reward_mint,
+        group_id: Pubkey::new_from_array(NULL_KEY_ARRAY),
+        reward_amount: reward_amount,
+        winner: winner,
+        reward_redeemed: false,
+        sponsor: Pubkey::new_unique(),
+        kind: kind,
+        pot_share_bps: 0,
+        picked_at: 0,
+        rerolls: 0,
+        tier: 0,
+    };
+}
+
+#[test]
+fn get_test_withdrawals_and_reserved_amount() {
+
+    let mut raffle = get_test_raffle();
+    let mint = Pubkey::new_unique();
+    let other_mint = Pubkey::new_unique();
+    let unsold_mint = Pubkey::new_unique();
+
+    // Two options sell in the same mint, a third in another one
+    let mut payment_options = get_test_weighted_payment_options();
+    payment_options.push(payment_options[0].clone());
+    payment_options[0].payment_mint = mint;
+    payment_options[0].amount_paid = 600;
+    payment_options[1].payment_mint = mint;
+    payment_options[1].amount_paid = 400;
+    payment_options[2].payment_mint = other_mint;
+    payment_options[2].amount_paid = 500;
+    raffle.payment_options = payment_options;
+
+    // A 25% pot share and an unclaimed SPL reward in the same mint, plus a claimed one that already left
+    let winner = Pubkey::new_unique();
+    let mut pot_share = get_test_reward(RewardKind::PotShare, mint, 0, winner);
+    pot_share.pot_share_bps = 2500;
+    let mut claimed = get_test_reward(RewardKind::Spl, mint, 70, winner);
+    claimed.reward_redeemed = true;
+    raffle.rewards = vec![
+        pot_share,
+        get_test_reward(RewardKind::Spl, mint, 50, winner),
+        claimed,
+        get_test_reward(RewardKind::Spl, unsold_mint, 10, winner),
+    ];
+
+    assert_eq!(get_owed_amount(&raffle, &mint), 750);
+    assert_eq!(get_reserved_amount(&raffle, &mint), 1050);
+    assert_eq!(get_reserved_amount(&raffle, &other_mint), 500);
+
+    // Partial withdrawals add up on one entry per mint
+    record_withdrawal(&mut raffle, &mint, 300, 750);
+    assert_eq!(get_withdrawn_amount(&raffle.withdrawals, &mint), 300);
+    assert_eq!(get_reserved_amount(&raffle, &mint), 750);
+    assert!(!raffle.payment_options[0].cashed_out);
+
+    record_withdrawal(&mut raffle, &mint, 750, 750);
+    assert_eq!(raffle.withdrawals.len(), 1);
+    assert_eq!(get_withdrawn_amount(&raffle.withdrawals, &mint), 750);
+    assert!(raffle.payment_options[0].cashed_out && raffle.payment_options[1].cashed_out);
+    assert!(!raffle.payment_options[2].cashed_out);
+
+    // The pot share and the unclaimed reward stay behind until they are redeemed
+    assert_eq!(get_reserved_amount(&raffle, &mint), 300);
+    raffle.rewards[0].reward_amount = get_reward_amount(&raffle, 0);
+    raffle.rewards[0].reward_redeemed = true;
+    assert_eq!(get_reserved_amount(&raffle, &mint), 50);
+    assert_eq!(get_reserved_amount(&raffle, &other_mint), 500);
+
+    // A mint with no sales only holds its rewards
+    assert_eq!(get_owed_amount(&raffle, &unsold_mint), 0);
+    assert_eq!(get_withdrawn_amount(&raffle.withdrawals, &unsold_mint), 0);
+    assert_eq!(get_reserved_amount(&raffle, &unsold_mint), 10);
+}
+
+#[test]
+fn get_test_free_tickets() {
+
+    let mut raffle = get_test_raffle();
+    let holder = Pubkey::new_unique();
+    let owner = raffle.owner;
+
+    assert_eq!(credit_free_tickets(&mut raffle, holder, 0), Err(ErrorCode::NeedFreeTickets.into()));
+    assert_eq!(credit_free_tickets(&mut raffle, owner, 1), Err(ErrorCode::BadBuyer.into()));
+    assert_eq!(credit_free_tickets(&mut raffle, holder, 11), Err(ErrorCode::BuyingTooMany.into()));
+
+    // Granted tickets count 1x and stack on the same holder
+    assert!(credit_free_tickets(&mut raffle, holder, 4).is_ok());
+    assert!(credit_free_tickets(&mut raffle, holder, 5).is_ok());
+    assert_eq!(raffle.holders.len(), 1);
+    assert_eq!(raffle.holders[0].tickets, 9);
+    assert_eq!(raffle.holders[0].entries, 9 * MAX_BPS as u32);
+    assert_eq!(raffle.free_tickets_granted, 9);
+
+    assert_eq!(credit_free_tickets(&mut raffle, holder, 2), Err(ErrorCode::BuyingTooMany.into()));
+    assert_eq!(credit_free_tickets(&mut raffle, Pubkey::new_unique(), 4), Err(ErrorCode::TooManyFreeTickets.into()));
+    assert_eq!(raffle.tickets_sold, 0);
+}
+
+#[test]
+fn get_test_reroll_and_reclaim_index() {
+
+    let mut raffle = get_test_raffle();
+    let winner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let picked_at = raffle.end_date + 100;
+
+    raffle.rewards = vec![
+        get_test_reward(RewardKind::Spl, mint, 1, winner),
+        get_test_reward(RewardKind::Spl, mint, 1, winner),
+        get_test_reward(RewardKind::Jackpot, mint, 1, winner),
+        get_test_reward(RewardKind::Spl, mint, 1, raffle.owner),
+        get_test_reward(RewardKind::Spl, mint, 1, Pubkey::new_from_array(NULL_KEY_ARRAY)),
+    ];
+    raffle.rewards[1].tier = 1;
+    for i in 0..raffle.rewards.len() {
+        raffle.rewards[i].picked_at = picked_at;
+    }
+
+    // Nothing until the reroll window has passed, then the lowest tier first
+    let window_end = picked_at + raffle.reroll_window;
+    assert_eq!(get_rerollable_reward_index(&raffle, window_end), raffle.rewards.len());
+    assert_eq!(get_rerollable_reward_index(&raffle, window_end + 1), 0);
+
+    // Jackpots, owner wins, undrawn and claimed rewards are never rerolled
+    raffle.rewards[0].reward_redeemed = true;
+    assert_eq!(get_rerollable_reward_index(&raffle, window_end + 1), 1);
+    raffle.rewards[1].rerolls = raffle.max_rerolls;
+    assert_eq!(get_rerollable_reward_index(&raffle, window_end + 1), raffle.rewards.len());
+
+    // A rerolled winner gets the full claim window from when they were picked
+    assert_eq!(get_claim_deadline(&raffle, 0), picked_at + raffle.claim_window);
+    raffle.rewards[0].picked_at = raffle.end_date - 10;
+    assert_eq!(get_claim_deadline(&raffle, 0), raffle.end_date + raffle.claim_window);
+}
+
+#[cfg(test)]
+fn get_test_mint_data(
+    extension_types: &[ExtensionType],
+) -> Vec<u8> {
+
+    let mut data = vec![0; ExtensionType::get_account_len::<spl_token_2022::state::Mint>(extension_types)];
+    let mut state = spl_token_2022::extension::StateWithExtensionsMut::<spl_token_2022::state::Mint>::unpack_uninitialized(&mut data).unwrap();
+    for i in 0..extension_types.len() {
+        match extension_types[i] {
+            ExtensionType::TransferFeeConfig => { state.init_extension::<spl_token_2022::extension::transfer_fee::TransferFeeConfig>().unwrap(); },
+            ExtensionType::DefaultAccountState => { state.init_extension::<DefaultAccountState>().unwrap().state = spl_token_2022::state::AccountState::Frozen as u8; },
+            _ => panic!("Unexpected extension"),
+        }
+    }
+    state.base.decimals = 6;
+    state.base.is_initialized = true;
+    state.pack_base();
+    state.init_account_type().unwrap();
+
+    return data;
+}
+
+#[test]
+fn get_test_token_2022_mint() {
+
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+
+    // Transfer fee mints are fine, their vaults need room for the withheld fees
+    let mut data = get_test_mint_data(&[ExtensionType::TransferFeeConfig]);
+    let mint = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &spl_token_2022::ID, false, 0);
+    assert_eq!(get_mint_decimals(&mint), Ok(6));
+    assert_eq!(
+        get_vault_size(&mint),
+        Ok(ExtensionType::get_account_len::<spl_token_2022::state::Account>(&[ExtensionType::TransferFeeAmount, ExtensionType::ImmutableOwner])),
+    );
+
+    // Only the two token programs
+    let mut data = get_test_mint_data(&[ExtensionType::TransferFeeConfig]);
+    let mint = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &key, false, 0);
+    assert_eq!(get_mint_decimals(&mint), Err(ErrorCode::BadTokenProgram.into()));
+
+    // Vaults that start frozen could never be paid into
+    let mut data = get_test_mint_data(&[ExtensionType::DefaultAccountState]);
+    let mint = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &spl_token_2022::ID, false, 0);
+    assert_eq!(get_mint_decimals(&mint), Err(ErrorCode::UnsupportedMint.into()));
+
+    // Extensions this program does not know about, like non-transferable (9) or transfer hooks (14)
+    let mut data = get_test_mint_data(&[ExtensionType::TransferFeeConfig]);
+    let tlv_start = <spl_token_2022::state::Account as solana_program::program_pack::Pack>::LEN + 1;
+    data[tlv_start..tlv_start + 2].copy_from_slice(&9u16.to_le_bytes());
+    let mint = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &spl_token_2022::ID, false, 0);
+    assert_eq!(get_mint_decimals(&mint), Err(ErrorCode::UnsupportedMint.into()));
+    assert_eq!(get_vault_size(&mint), Err(ErrorCode::UnsupportedMint.into()));
+
+    // Classic vaults are the usual size and address
+    let mut data = vec![0; <spl_token_2022::state::Mint as solana_program::program_pack::Pack>::LEN];
+    let mint = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &anchor_spl::token::ID, false, 0);
+    assert_eq!(get_vault_size(&mint), Ok(<spl_token_2022::state::Account as solana_program::program_pack::Pack>::LEN));
+
+    let wallet = Pubkey::new_unique();
+    assert_eq!(get_vault_address(&wallet, &key, &anchor_spl::token::ID), get_associated_token_address(&wallet, &key));
+    assert_ne!(get_vault_address(&wallet, &key, &spl_token_2022::ID), get_associated_token_address(&wallet, &key));
+}
 
+#[test]
+fn get_test_pnft_transfer_instruction() {
+
+    let mint = Pubkey::new_unique();
+    let gatekeeper = Pubkey::new_unique();
+    let winner = Pubkey::new_unique();
+    let payer = Pubkey::new_unique();
+    let reward_vault = get_associated_token_address(&gatekeeper, &mint);
+    let winner_vault = get_associated_token_address(&winner, &mint);
+
+    // Transfer V1 of a single token, without authorization data
+    let instruction = get_pnft_transfer_instruction(&reward_vault, &gatekeeper, &winner_vault, &winner, &mint, &payer, &token_metadata::ID);
+    assert_eq!(instruction.program_id, token_metadata::ID);
+    assert_eq!(instruction.data, vec![49, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(instruction.accounts.len(), 17);
+
+    // Token records are per token account, the gatekeeper signs as the token's owner
+    assert_eq!(instruction.accounts[5].pubkey, Pubkey::find_program_address(&[b"metadata", token_metadata::ID.as_ref(), mint.as_ref()], &token_metadata::ID).0);
+    assert_eq!(instruction.accounts[6].pubkey, Pubkey::find_program_address(&[b"metadata", token_metadata::ID.as_ref(), mint.as_ref(), b"edition"], &token_metadata::ID).0);
+    assert_eq!(instruction.accounts[7].pubkey, get_token_record_address(&mint, &reward_vault));
+    assert_eq!(instruction.accounts[8].pubkey, get_token_record_address(&mint, &winner_vault));
+    assert_ne!(instruction.accounts[7].pubkey, instruction.accounts[8].pubkey);
+    assert!(instruction.accounts[9].pubkey == gatekeeper && instruction.accounts[9].is_signer && !instruction.accounts[9].is_writable);
+    assert!(instruction.accounts[10].pubkey == payer && instruction.accounts[10].is_signer && instruction.accounts[10].is_writable);
+    for i in [0, 2, 5, 7, 8] {
+        assert!(instruction.accounts[i].is_writable);
+    }
 
+    // Without a rule set both rule accounts are left out
+    assert_eq!(instruction.accounts[15].pubkey, token_metadata::ID);
+    assert_eq!(instruction.accounts[16].pubkey, token_metadata::ID);
+
+    // With one, Token Auth Rules checks it
+    let rule_set = Pubkey::new_unique();
+    let instruction = get_pnft_transfer_instruction(&reward_vault, &gatekeeper, &winner_vault, &winner, &mint, &payer, &rule_set);
+    assert_eq!(instruction.accounts[15].pubkey, token_auth_rules::ID);
+    assert_eq!(instruction.accounts[16].pubkey, rule_set);
+}
@@ -0,0 +1,214 @@
+import * as anchor from "@project-serum/anchor";
+import * as spl from "@solana/spl-token";
+import * as helpers from "./solHelpers";
+import * as Raffle from "../ts/solapeRaffle";
+import NodeWallet from "@project-serum/anchor/dist/cjs/nodewallet";
+import { assert } from "chai";
+
+// Runs against `anchor localnet`, which deploys the raffle and clones Token Metadata and Token Auth Rules
+const secretArray = require('/Users/drkrueger/.config/solana/id.json');
+const secret = new Uint8Array(secretArray);
+const payerKeypair = anchor.web3.Keypair.fromSecretKey(secret);
+
+const LOCALNET = "http://127.0.0.1:8899";
+
+// --------- PROGRAMMABLE NFT TOOLS -----------------------------------------
+const _borshString = (value: string) => {
+  let length = Buffer.alloc(4);
+  length.writeUInt32LE(value.length, 0);
+  return Buffer.concat([length, Buffer.from(value)]);
+}
+
+const _meta = (pubkey: anchor.web3.PublicKey, isSigner: boolean, isWritable: boolean) => {
+  return { pubkey, isSigner, isWritable };
+}
+
+// Token Metadata's Create (V1) and Mint (V1), the NFT has no rule set so every transfer is allowed
+const createPNFT = async (provider: anchor.Provider) => {
+  const mintKeypair = anchor.web3.Keypair.generate();
+  const mint = mintKeypair.publicKey;
+  const owner = provider.wallet.publicKey;
+  const vault = await helpers.getAssociatedTokenAddress(mint, owner);
+  const metadata = Raffle.getMetadataAddress(mint);
+  const edition = Raffle.getEditionAddress(mint);
+  const tmID = Raffle.TOKEN_METADATA_PROGRAM_ID;
+
+  let sellerFee = Buffer.alloc(2);
+  sellerFee.writeUInt16LE(0, 0);
+  const createData = Buffer.concat([
+    Buffer.from([42, 0]), // Create, V1
+    _borshString("Test pNFT"),
+    _borshString("TPNFT"),
+    _borshString(""),
+    sellerFee,
+    Buffer.from([
+      0, // creators
+      0, // primary sale happened
+      1, // is mutable
+      4, // ProgrammableNonFungible
+      0, // collection
+      0, // uses
+      0, // collection details
+      0, // rule set
+      1, 0, // decimals
+      1, 0, // print supply, zero
+    ]),
+  ]);
+
+  let amount = Buffer.alloc(8);
+  amount.writeBigUInt64LE(BigInt(1), 0);
+  const mintData = Buffer.concat([
+    Buffer.from([43, 0]), // Mint, V1
+    amount,
+    Buffer.from([0]), // authorization data
+  ]);
+
+  let tx = new anchor.web3.Transaction();
+  tx.add(
+    new anchor.web3.TransactionInstruction({
+      programId: tmID,
+      keys: [
+        _meta(metadata, false, true),
+        _meta(edition, false, true),
+        _meta(mint, true, true),
+        _meta(owner, true, false),
+        _meta(owner, true, true),
+        _meta(owner, true, false),
+        _meta(anchor.web3.SystemProgram.programId, false, false),
+        _meta(anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY, false, false),
+        _meta(spl.TOKEN_PROGRAM_ID, false, false),
+      ],
+      data: createData,
+    }),
+    new anchor.web3.TransactionInstruction({
+      programId: tmID,
+      keys: [
+        _meta(vault, false, true),
+        _meta(owner, false, false),
+        _meta(metadata, false, false),
+        _meta(edition, false, false),
+        _meta(Raffle.getTokenRecordAddress(mint, vault), false, true),
+        _meta(mint, false, true),
+        _meta(owner, true, false),
+        _meta(tmID, false, false),
+        _meta(owner, true, true),
+        _meta(anchor.web3.SystemProgram.programId, false, false),
+        _meta(anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY, false, false),
+        _meta(spl.TOKEN_PROGRAM_ID, false, false),
+        _meta(spl.ASSOCIATED_TOKEN_PROGRAM_ID, false, false),
+        _meta(tmID, false, false),
+        _meta(tmID, false, false),
+      ],
+      data: mintData,
+    }),
+  );
+
+  await provider.send(tx, [mintKeypair]);
+
+  return { mint, vault };
+}
+
+const expectError = async (call: Promise<any>, error: string) => {
+  try {
+    await call;
+  } catch (e) {
+    assert.include(e.toString(), error);
+    return;
+  }
+  assert.fail("Expected " + error);
+}
+
+// MAIN
+const main = async() => {
+  console.log("🚀 Starting pNFT test...\n\n");
+
+  const ownerWallet = new NodeWallet(payerKeypair);
+  const ownerProvider = new anchor.Provider(
+    new anchor.web3.Connection(LOCALNET, "confirmed"),
+    ownerWallet,
+    { commitment: "confirmed" },
+  );
+  anchor.setProvider(ownerProvider);
+  const ownerRaffleProvider = Raffle.RaffleProvider.createLocal(ownerProvider, anchor.workspace.Raffle);
+
+  const holderWallet = new NodeWallet(anchor.web3.Keypair.generate());
+  const holderProvider = new anchor.Provider(ownerProvider.connection, holderWallet, { commitment: "confirmed" });
+  const holderRaffleProvider = Raffle.RaffleProvider.createLocal(holderProvider, anchor.workspace.Raffle);
+  await ownerProvider.connection.confirmTransaction(
+    await ownerProvider.connection.requestAirdrop(holderWallet.publicKey, anchor.web3.LAMPORTS_PER_SOL),
+  );
+
+  console.log("creating pNFT");
+  const pnft = await createPNFT(ownerProvider);
+  const ownerVault = await helpers.getSPLAccount(ownerProvider, pnft.mint, pnft.vault);
+  assert.equal(ownerVault.amount.toNumber(), 1);
+  assert.isTrue(ownerVault.isFrozen);
+
+  // Switchboard is not on the local validator, the RNG accounts are only stored here
+  console.log("creating master raffle");
+  const masterRaffleKey = Raffle.getMasterRaffleAddress(ownerWallet.publicKey, 0);
+  const [bot, botBump] = anchor.utils.publicKey.findProgramAddressSync(
+    [Buffer.from("SOLAPE"), masterRaffleKey.toBytes(), ownerWallet.publicKey.toBytes()],
+    Raffle.RAFFLE_ID,
+  );
+  const masterRaffle = await Raffle.createMasterRaffle(
+    ownerRaffleProvider,
+    10,
+    "Test Master Raffle",
+    "Test BOT",
+    0,
+    {
+      oracle: anchor.web3.Keypair.generate().publicKey,
+      vrf: anchor.web3.Keypair.generate().publicKey,
+      bot: bot,
+      botBump: botBump,
+    },
+  );
+
+  console.log("creating raffle");
+  const testPaymentSPL = await helpers.createSPL(ownerProvider, 10000);
+  const paymentOptions = [Raffle.createPaymentOption("Test SPL", testPaymentSPL.mint, new anchor.BN(10), 1)];
+  const rewards = [Raffle.createPnftReward("Test pNFT", pnft.mint)];
+  let raffle = await Raffle.createRaffleAccount(
+    ownerRaffleProvider,
+    masterRaffle,
+    paymentOptions,
+    rewards,
+    "Test pNFT Raffle",
+  );
+
+  console.log("plain SPL loads are rejected");
+  await expectError(
+    Raffle.loadReward(ownerRaffleProvider, raffle, Raffle.createReward("Test pNFT", pnft.mint, new anchor.BN(1))),
+    "Reward vault is frozen",
+  );
+
+  console.log("loading pNFT through Token Metadata");
+  raffle = await Raffle.loadPnftReward(ownerRaffleProvider, raffle, rewards[0]);
+  const rewardVaultKey = await helpers.getAssociatedTokenAddress(pnft.mint, raffle.gatekeeper, true);
+  const rewardVault = await helpers.getSPLAccount(ownerProvider, pnft.mint, rewardVaultKey);
+  assert.equal(rewardVault.amount.toNumber(), 1);
+  assert.isTrue(rewardVault.isFrozen);
+  assert.equal((await helpers.getSPLAccount(ownerProvider, pnft.mint, pnft.vault)).amount.toNumber(), 0);
+  assert.isDefined(raffle.rewards[0].kind.programmableNft);
+  assert.isNotNull(await ownerProvider.connection.getAccountInfo(Raffle.getTokenRecordAddress(pnft.mint, rewardVaultKey)));
+
+  console.log("only the winner can take it out");
+  await expectError(
+    Raffle.redeemPnftReward(holderRaffleProvider, raffle, 0),
+    "No rewards left for this winner",
+  );
+  await expectError(
+    Raffle.distributePnftReward(holderRaffleProvider, raffle, 0),
+    "Winner cannot be 0'd pubkey",
+  );
+  await expectError(
+    Raffle.reclaimExpiredPnftReward(ownerRaffleProvider, raffle, 0),
+    "Winner cannot be 0'd pubkey",
+  );
+  assert.equal((await helpers.getSPLAccount(ownerProvider, pnft.mint, rewardVaultKey)).amount.toNumber(), 1);
+
+  console.log("... to the moon! 🌑");
+}
+
+main();
@@ -76,7 +76,7 @@ const main = async() => {
   console.log("creating master raffle");
   const masterRaffle = await Raffle.createMasterRaffle(
     ownerRaffleProvider,
-    1000,
+    250,
     "Test Master Raffle",
     "Test BOT",
   );
@@ -227,8 +227,8 @@ const main = async() => {
 
           break;
         case 'c':
-          console.log("cash out owner...");
-          await Raffle.cashOutOwner(
+          console.log("distribute proceeds...");
+          await Raffle.distributeProceeds(
             optionRaffleProvider,
             raffle,
             option,
@@ -242,6 +242,14 @@ const main = async() => {
             option,
           )
           break;
+        case 'd':
+          console.log("distribute reward...");
+          raffle = await Raffle.distributeReward(
+            optionRaffleProvider,
+            raffle,
+            option,
+          )
+          break;
         case 's':
           console.log("starting raffle...");
           raffle = await Raffle.startRaffle(
@@ -250,6 +258,28 @@ const main = async() => {
             new Date(Date.now() + 1000 * 60 * 1)
           );
           break;
+        case 'ue':
+          console.log("updating end date...");
+          raffle = await Raffle.updateEndDate(
+            optionRaffleProvider,
+            raffle,
+            new Date(Date.now() + 1000 * 60 * option)
+          );
+          break;
+        case 'p':
+          console.log("pausing raffle...");
+          raffle = await Raffle.pauseRaffle(
+            optionRaffleProvider,
+            raffle,
+          );
+          break;
+        case 'u':
+          console.log("resuming raffle...");
+          raffle = await Raffle.resumeRaffle(
+            optionRaffleProvider,
+            raffle,
+          );
+          break;
         case 'b':
           console.log("buying tickets for " + optionRaffleProvider.provider.wallet.publicKey + "...");
           raffle = await Raffle.buyTickets(
@@ -16,36 +16,76 @@ import {
 // --------- DEFINES -----------------------------------------
 export const RAFFLE_ID = new anchor.web3.PublicKey("4iMPsUWtpnNQhjhs1gSw74j5arJpxXa6DrUWYKXsgWVn");
 export const SWITCHBOARD_ID = SBV2_DEVNET_PID;
+export const TOKEN_2022_PROGRAM_ID = new anchor.web3.PublicKey("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+export const TOKEN_METADATA_PROGRAM_ID = new anchor.web3.PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+export const TOKEN_AUTH_RULES_PROGRAM_ID = new anchor.web3.PublicKey("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
+
+export const MASTER_RAFFLE_SEED = "MASTER_RAFFLE";
+export const RAFFLE_SEED = "RAFFLE";
+export const JACKPOT_SEED = "JACKPOT";
+export const TEMPLATE_SEED = "TEMPLATE";
+export const VOUCHER_SEED = "VOUCHER";
+export const VOUCHERS_PER_BITMAP = 8192;
+export const DEFAULT_CLAIM_WINDOW = 15778476; // 6 Months, can be 1 day to 1 year
+export const DEFAULT_TICKET_WEIGHT_BPS = 10000; // 1x, 15000 makes each ticket count 1.5x in the draw
 
-export const MASTER_RAFFLE_ACCOUNT_BASE_SIZE = (
-    8 + 64 + (32 * 5) + 2 + 4
-)
 export interface MasterRaffleAccount {
     name: string,
     masterRaffle: anchor.web3.PublicKey,
     owner: anchor.web3.PublicKey,
+    index: anchor.BN,
     maxRaffles: number,
+    raffleCounter: anchor.BN,
     oracle: anchor.web3.PublicKey,
     vrfAccount: anchor.web3.PublicKey,
     rngBot: anchor.web3.PublicKey,
     raffles: anchor.web3.PublicKey[],
+    paused: boolean,
+    operator: anchor.web3.PublicKey,
+    voucherAuthority: anchor.web3.PublicKey,
+    jackpotKeeper: anchor.web3.PublicKey,
+    jackpotNonce: number,
+    jackpotMint: anchor.web3.PublicKey,
+    jackpotBps: number,
+    jackpotOddsBps: number,
+    jackpotBalance: anchor.BN,
+}
+
+export interface RafflePayee {
+    recipient: anchor.web3.PublicKey,
+    shareBps: number,
+}
+
+export interface RaffleMintWithdrawal {
+    mint: anchor.web3.PublicKey,
+    amount: anchor.BN,
+}
+
+export interface PriceStep {
+    startsAt: anchor.BN,
+    paymentAmount: anchor.BN,
 }
 
-export const RAFFLE_PAYMENT_OPTION_SIZE = (
-    64 + (32 * 1) + 8 + 1 + 8 + 1
-)
 export interface RafflePaymentOption {
     name: string,
     paymentMint: anchor.web3.PublicKey,
     paymentAmount: anchor.BN,
     ticketsPerPayment: number,
+    weightBps?: number,
+    endPaymentAmount?: anchor.BN,
+    priceSteps?: PriceStep[],
+    maxPurchases?: anchor.BN,
     paymentTally?: anchor.BN,
+    amountPaid?: anchor.BN,
     cashedOut?: boolean,
 }
 
-export const RAFFLE_REWARD_SIZE = (
-    64 + (32 * 3) + 8 + 1
-)
+export const REWARD_KIND_SPL = { spl: {} };
+export const REWARD_KIND_LAMPORTS = { lamports: {} };
+export const REWARD_KIND_POT_SHARE = { potShare: {} };
+export const REWARD_KIND_JACKPOT = { jackpot: {} };
+export const REWARD_KIND_PROGRAMMABLE_NFT = { programmableNft: {} };
+
 export interface RaffleReward {
     name: string,
     rewardMint: anchor.web3.PublicKey,
@@ -53,19 +93,21 @@ export interface RaffleReward {
     rewardAmount: anchor.BN,
     winner?: anchor.web3.PublicKey,
     rewardRedeemed?: boolean,
+    sponsor?: anchor.web3.PublicKey,
+    kind?: any,
+    potShareBps?: number,
+    pickedAt?: anchor.BN,
+    rerolls?: number,
+    tier?: number,
 }
 
-export const RAFFLE_TICKET_HOLDER_SIZE = (
-    (32 * 1) + 1
-)
 export interface TicketHolder {
     holder: anchor.web3.PublicKey,
-    tickets: number
+    tickets: number,
+    entries: number,
+    optionTickets: number[],
 }
 
-export const RAFFLE_ACCOUNT_BASE_SIZE = (
-    8 + 64 + (32 * 5) + (1 * 4) + (2 * 1) + (2 * 8) + (4 * 3)
-)
 export interface RaffleAccount {
     name: string,
     masterRaffle: anchor.web3.PublicKey,
@@ -73,16 +115,62 @@ export interface RaffleAccount {
     owner: anchor.web3.PublicKey,
     gatekeeper: anchor.web3.PublicKey,
     nonce: number,
+    index: anchor.BN,
     maxTicketsPerHolder: number,
     paymentOptionCount: number,
     rewardCount: number,
     maxHolderCount: number,
+    maxSponsorCount: number,
+    maxFreeTickets: number,
+    freeTicketsGranted: number,
+    maxRerolls: number,
+    rerollWindow: anchor.BN,
+    claimWindow: anchor.BN,
+    maxTotalTickets: number,
+    ticketsSold: number,
     startDate: anchor.BN,
     endDate: anchor.BN,
+    rampEndDate: anchor.BN,
+    endDateUpdates: number,
+    pausedAt: anchor.BN,
+    pauseCount: number,
+    totalPausedDuration: anchor.BN,
     paymentOptions: RafflePaymentOption[],
     rewards: RaffleReward[],
+    sponsors: anchor.web3.PublicKey[],
     holders: TicketHolder[],
     rngBot: anchor.web3.PublicKey,
+    jackpotBps: number,
+    crankFee: anchor.BN,
+    crankFeeBalance: anchor.BN,
+    dustRecipient: anchor.web3.PublicKey,
+    payees: RafflePayee[],
+    withdrawals: RaffleMintWithdrawal[],
+}
+
+export interface RaffleTemplateAccount {
+    name: string,
+    template: anchor.web3.PublicKey,
+    masterRaffle: anchor.web3.PublicKey,
+    owner: anchor.web3.PublicKey,
+    keeper: anchor.web3.PublicKey,
+    nonce: number,
+    index: anchor.BN,
+    maxTicketsPerHolder: number,
+    paymentOptionCount: number,
+    rewardCount: number,
+    maxHolderCount: number,
+    claimWindow: anchor.BN,
+    maxTotalTickets: number,
+    duration: anchor.BN,
+    active: boolean,
+    lastRaffle: anchor.web3.PublicKey,
+    lastEndDate: anchor.BN,
+    rounds: anchor.BN,
+    dustRecipient: anchor.web3.PublicKey,
+    payees: RafflePayee[],
+    paymentOptions: RafflePaymentOption[],
+    rewards: RaffleReward[],
 }
 
 
@@ -112,6 +200,15 @@ export class RaffleProvider {
         );
     }
 
+    // Local validators have no IDLs on chain and no Switchboard, so RNG calls are not available
+    static createLocal = (provider: anchor.Provider, raffleProgram: anchor.Program<anchor.Idl>) => {
+        return new RaffleProvider(
+            provider,
+            raffleProgram,
+            undefined,
+        );
+    }
+
     static _getRaffleProgram = (provider: anchor.Provider) => { return _getProgram(provider, RAFFLE_ID); }
     static _getSwitchboardProgram = (provider: anchor.Provider) => { return _getProgram(provider, SWITCHBOARD_ID); }
        
@@ -142,6 +239,20 @@ export class RaffleProvider {
         }
         return (await this.raffleProgram.account.raffle.fetch(raffleKey as anchor.web3.PublicKey)) as RaffleAccount; 
     }
+
+    async getTemplateAccount(
+        templateKey: anchor.web3.PublicKey | RaffleTemplateAccount,
+        shouldUpdate?: boolean,
+    ) { 
+        if((templateKey as RaffleTemplateAccount).name){
+            if( shouldUpdate ){
+                return (await this.raffleProgram.account.raffleTemplate.fetch((templateKey as RaffleTemplateAccount).template)) as RaffleTemplateAccount; 
+            } else {
+                return await templateKey as RaffleTemplateAccount;
+            }
+        }
+        return (await this.raffleProgram.account.raffleTemplate.fetch(templateKey as anchor.web3.PublicKey)) as RaffleTemplateAccount; 
+    }
 }
 
 export interface RNGAccounts {
@@ -286,44 +397,180 @@ export const createRNGAccounts = async (
 }
 
 
+export const getMasterRaffleAddress = (
+    owner: anchor.web3.PublicKey,
+    index: number | anchor.BN,
+) => {
+    const [masterRaffle] = anchor.utils.publicKey.findProgramAddressSync(
+        [
+            Buffer.from(MASTER_RAFFLE_SEED),
+            owner.toBytes(),
+            new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+        ],
+        RAFFLE_ID,
+    );
+    return masterRaffle;
+}
+
+export const getRaffleAddress = (
+    masterRaffle: anchor.web3.PublicKey,
+    index: number | anchor.BN,
+) => {
+    const [raffle] = anchor.utils.publicKey.findProgramAddressSync(
+        [
+            Buffer.from(RAFFLE_SEED),
+            masterRaffle.toBytes(),
+            new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+        ],
+        RAFFLE_ID,
+    );
+    return raffle;
+}
+
+export const getGatekeeperAddress = (
+    raffle: anchor.web3.PublicKey,
+) => {
+    const [gatekeeper] = anchor.utils.publicKey.findProgramAddressSync(
+        [raffle.toBytes()],
+        RAFFLE_ID,
+    );
+    return gatekeeper;
+}
+
+export const getJackpotKeeperAddress = (
+    masterRaffle: anchor.web3.PublicKey,
+) => {
+    const [jackpotKeeper] = anchor.utils.publicKey.findProgramAddressSync(
+        [
+            Buffer.from(JACKPOT_SEED),
+            masterRaffle.toBytes(),
+        ],
+        RAFFLE_ID,
+    );
+    return jackpotKeeper;
+}
+
+export const getTemplateAddress = (
+    masterRaffle: anchor.web3.PublicKey,
+    index: number | anchor.BN,
+) => {
+    const [template] = anchor.utils.publicKey.findProgramAddressSync(
+        [
+            Buffer.from(TEMPLATE_SEED),
+            masterRaffle.toBytes(),
+            new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+        ],
+        RAFFLE_ID,
+    );
+    return template;
+}
+
+// Template keepers are derived the same way as gatekeepers
+export const getTemplateKeeperAddress = (
+    template: anchor.web3.PublicKey,
+) => {
+    return getGatekeeperAddress(template);
+}
+
+// Every raffle a master has ever created, including ones removed from its index
+// Token Metadata's PDAs, the token record tracks the state of one token account of a programmable NFT
+export const getMetadataAddress = (
+    mint: anchor.web3.PublicKey,
+) => {
+    const [metadata] = anchor.utils.publicKey.findProgramAddressSync(
+        [
+            Buffer.from("metadata"),
+            TOKEN_METADATA_PROGRAM_ID.toBytes(),
+            mint.toBytes(),
+        ],
+        TOKEN_METADATA_PROGRAM_ID,
+    );
+    return metadata;
+}
+
+export const getEditionAddress = (
+    mint: anchor.web3.PublicKey,
+) => {
+    const [edition] = anchor.utils.publicKey.findProgramAddressSync(
+        [
+            Buffer.from("metadata"),
+            TOKEN_METADATA_PROGRAM_ID.toBytes(),
+            mint.toBytes(),
+            Buffer.from("edition"),
+        ],
+        TOKEN_METADATA_PROGRAM_ID,
+    );
+    return edition;
+}
+
+export const getTokenRecordAddress = (
+    mint: anchor.web3.PublicKey,
+    token: anchor.web3.PublicKey,
+) => {
+    const [tokenRecord] = anchor.utils.publicKey.findProgramAddressSync(
+        [
+            Buffer.from("metadata"),
+            TOKEN_METADATA_PROGRAM_ID.toBytes(),
+            mint.toBytes(),
+            Buffer.from("token_record"),
+            token.toBytes(),
+        ],
+        TOKEN_METADATA_PROGRAM_ID,
+    );
+    return tokenRecord;
+}
+
+export const getAllRaffleAddresses = async (
+    raffleProvider: RaffleProvider,
+    masterRaffleAccount: anchor.web3.PublicKey | MasterRaffleAccount,
+) => {
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(masterRaffleAccount, true);
+    let raffles = [] as anchor.web3.PublicKey[];
+
+    for(var i = 0; i < masterRaffle.raffleCounter.toNumber(); i++){
+        raffles.push(getRaffleAddress(masterRaffle.masterRaffle, i));
+    }
+
+    return raffles;
+}
+
 export const createMasterRaffle = async (
     raffleProvider: RaffleProvider, 
     maxRaffles?:number,
     masterRaffleName?: string,
     botName?: string,
-    masterRaffleKeypair?: anchor.web3.Keypair,
+    index?: number,
     rngAccounts?: RNGAccounts,
 ) => {
     const raffleProgram = raffleProvider.raffleProgram;
-    const masterRaffle = masterRaffleKeypair ?? anchor.web3.Keypair.generate();
-    const rng = rngAccounts ?? await createRNGAccounts(raffleProvider, masterRaffle.publicKey);
     const owner = raffleProvider.provider.wallet;
-    const raffleCount = maxRaffles ?? 512;
+    const masterIndex = index ?? 0;
+    const masterRaffle = getMasterRaffleAddress(owner.publicKey, masterIndex);
+    const rng = rngAccounts ?? await createRNGAccounts(raffleProvider, masterRaffle);
+    const raffleCount = maxRaffles ?? 256;
 
     await raffleProgram.rpc.createMasterRaffle(
         {
+            index: new anchor.BN(masterIndex),
             name: masterRaffleName ?? "SOLAPE Master Raffle",
             maxRaffles: raffleCount,
-            botBump: rng.botBump,
             botName: botName ?? "SOLAPE RNG bot",
         },
         {
             accounts: {
-                masterRaffle: masterRaffle.publicKey,
+                masterRaffle: masterRaffle,
                 oracle: rng.oracle,
                 vrfAccount: rng.vrf,
                 rngBot: rng.bot,
                 owner: owner.publicKey,
                 systemProgram: anchor.web3.SystemProgram.programId,
             },
-            signers: [masterRaffle],
-            instructions: [
-                await raffleProgram.account.masterRaffle.createInstruction(masterRaffle, _masterRaffleAccountSize(raffleCount)),
-            ],
+            signers: [],
+            instructions: [],
         }
     );
 
-    return await raffleProvider.getMasterRaffleAccount(masterRaffle.publicKey, true);
+    return await raffleProvider.getMasterRaffleAccount(masterRaffle, true);
 }
 
 export const createPaymentOption = (
@@ -331,12 +578,20 @@ export const createPaymentOption = (
     paymentMint: anchor.web3.PublicKey,
     paymentAmount: anchor.BN,
     ticketsPerPayment: number,
+    weightBps?: number,
+    endPaymentAmount?: anchor.BN,
+    priceSteps?: PriceStep[],
+    maxPurchases?: number,
 ) => {
     const option: RafflePaymentOption = {
         name: name,
         paymentMint: paymentMint,
         paymentAmount: paymentAmount,
-        ticketsPerPayment: ticketsPerPayment
+        ticketsPerPayment: ticketsPerPayment,
+        weightBps: weightBps ?? DEFAULT_TICKET_WEIGHT_BPS,
+        endPaymentAmount: endPaymentAmount ?? new anchor.BN(0),
+        priceSteps: priceSteps ?? [],
+        maxPurchases: new anchor.BN(maxPurchases ?? 0),
     };
     return option;
 }
@@ -346,12 +601,85 @@ export const createReward = (
     rewardMint: anchor.web3.PublicKey,
     rewardAmount: anchor.BN,
     groupId?: anchor.web3.PublicKey,
+    tier?: number,
 ) => {
     const reward: RaffleReward = {
         name: name,
         rewardMint: rewardMint,
         rewardAmount: rewardAmount,
         groupId: groupId ?? anchor.web3.Keypair.generate().publicKey,
+        tier: tier ?? 0,
+    };
+    return reward;
+}
+
+export const createSolReward = (
+    name: string,
+    lamports: anchor.BN,
+    groupId?: anchor.web3.PublicKey,
+    tier?: number,
+) => {
+    const reward: RaffleReward = {
+        name: name,
+        rewardMint: anchor.web3.PublicKey.default,
+        rewardAmount: lamports,
+        groupId: groupId ?? anchor.web3.Keypair.generate().publicKey,
+        kind: REWARD_KIND_LAMPORTS,
+        tier: tier ?? 0,
+    };
+    return reward;
+}
+
+// A share of everything collected in paymentMint, 5000 bps is a 50/50 raffle
+export const createPotReward = (
+    name: string,
+    paymentMint: anchor.web3.PublicKey,
+    potShareBps: number,
+    groupId?: anchor.web3.PublicKey,
+    tier?: number,
+) => {
+    const reward: RaffleReward = {
+        name: name,
+        rewardMint: paymentMint,
+        rewardAmount: new anchor.BN(0),
+        groupId: groupId ?? anchor.web3.Keypair.generate().publicKey,
+        kind: REWARD_KIND_POT_SHARE,
+        potShareBps: potShareBps,
+        tier: tier ?? 0,
+    };
+    return reward;
+}
+
+// Only won with the master's jackpot odds, otherwise the jackpot carries over
+export const createJackpotReward = (
+    name: string,
+    tier?: number,
+) => {
+    const reward: RaffleReward = {
+        name: name,
+        rewardMint: anchor.web3.PublicKey.default,
+        rewardAmount: new anchor.BN(0),
+        groupId: anchor.web3.PublicKey.default,
+        kind: REWARD_KIND_JACKPOT,
+        tier: tier ?? 0,
+    };
+    return reward;
+}
+
+// Metaplex programmable NFT, always moved through Token Metadata
+export const createPnftReward = (
+    name: string,
+    rewardMint: anchor.web3.PublicKey,
+    groupId?: anchor.web3.PublicKey,
+    tier?: number,
+) => {
+    const reward: RaffleReward = {
+        name: name,
+        rewardMint: rewardMint,
+        rewardAmount: new anchor.BN(1),
+        groupId: groupId ?? anchor.web3.Keypair.generate().publicKey,
+        kind: REWARD_KIND_PROGRAMMABLE_NFT,
+        tier: tier ?? 0,
     };
     return reward;
 }
@@ -362,6 +690,7 @@ export const createBasketReward = (
     rewardAmount: anchor.BN[],
     rewardsToGroup?: RaffleReward[],
     groupId?: anchor.web3.PublicKey,
+    tier?: number,
 ) => {
     let rewards = [] as RaffleReward[];
     const id = groupId ?? anchor.web3.Keypair.generate().publicKey;
@@ -373,6 +702,7 @@ export const createBasketReward = (
                 rewardMint: rewardsToGroup[i].rewardMint,
                 rewardAmount: rewardsToGroup[i].rewardAmount,
                 groupId: id,
+                tier: tier ?? rewardsToGroup[i].tier ?? 0,
             });
         }
     } else {
@@ -388,6 +718,7 @@ export const createBasketReward = (
                 rewardMint: rewardMint[i],
                 rewardAmount: rewardAmount[i],
                 groupId: id,
+                tier: tier ?? 0,
             });
         }
     }
@@ -403,52 +734,80 @@ export const createRaffleAccount = async (
     raffleName?: string,
     maxHolderCount?: number,
     maxTicketsPerHolder?: number,
-    raffleKeypair?: anchor.web3.Keypair,
+    maxSponsorCount?: number,
+    maxFreeTickets?: number,
+    maxRerolls?: number,
+    rerollWindow?: number,
+    claimWindow?: number,
+    maxTotalTickets?: number,
 ) => {
     const raffleProgram = raffleProvider.raffleProgram;
-    const raffle = raffleKeypair ?? anchor.web3.Keypair.generate();
-    const masterRaffle = await raffleProvider.getMasterRaffleAccount(masterRaffleAccount);
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(masterRaffleAccount, true);
+    const raffle = getRaffleAddress(masterRaffle.masterRaffle, masterRaffle.raffleCounter);
+    const gatekeeper = getGatekeeperAddress(raffle);
     const owner = raffleProvider.provider.wallet;
     const maxHolders = maxHolderCount ?? 1000;
 
-    const [gatekeeper, nonce] = await anchor.web3.PublicKey.findProgramAddress(
-        [raffle.publicKey.toBuffer()],
-        raffleProgram.programId,
-    );
-
     await raffleProgram.rpc.createRaffle(
         {
-            nonce: nonce,
             name: raffleName ?? "SOLAPE Raffle",
             maxTicketsPerHolder: maxTicketsPerHolder ?? 0xFF,
             paymentOptionCount: paymentOptions.length,
             rewardCount: rewards.length,
             maxHolderCount: maxHolders,
+            maxSponsorCount: maxSponsorCount ?? 0,
+            maxFreeTickets: maxFreeTickets ?? 0,
+            maxRerolls: maxRerolls ?? 0,
+            rerollWindow: new anchor.BN(rerollWindow ?? 0),
+            claimWindow: new anchor.BN(claimWindow ?? DEFAULT_CLAIM_WINDOW),
+            maxTotalTickets: maxTotalTickets ?? 0,
         },
         {
             accounts: {
-                raffle: raffle.publicKey,
+                raffle: raffle,
                 gatekeeper: gatekeeper,
                 masterRaffle: masterRaffle.masterRaffle,
                 rngBot: masterRaffle.rngBot,
                 owner: owner.publicKey,
                 systemProgram: anchor.web3.SystemProgram.programId,
             },
-            signers: [raffle],
-            instructions: [
-                await raffleProgram.account.raffle.createInstruction(
-                    raffle, 
-                    _raffleAccountSize(
-                        paymentOptions.length,
-                        rewards.length,
-                        maxHolders,
-                    )
-                ),
-            ],
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    // Raffles are created at up to 10KB, grow them the rest of the way
+    let size = (await raffleProvider.provider.connection.getAccountInfo(raffle)).data.length;
+    while( true ) {
+        await expandRaffle(raffleProvider, raffle);
+        const newSize = (await raffleProvider.provider.connection.getAccountInfo(raffle)).data.length;
+        if( newSize === size ) { break; }
+        size = newSize;
+    }
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const expandRaffle = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+
+    await raffleProgram.rpc.expandRaffle(
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                owner: raffle.owner,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            },
+            signers: [],
+            instructions: [],
         }
     );
 
-    return await raffleProvider.getRaffleAccount(raffle.publicKey, true);
+    return await raffleProvider.getRaffleAccount(raffle, true);
 }
 
 export const loadPaymentOption = async (
@@ -459,7 +818,7 @@ export const loadPaymentOption = async (
     const raffleProgram = raffleProvider.raffleProgram;
     const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
 
-    const {vault, shouldCreate} = await _getAssociatedTokenAddressAndShouldCreate(
+    const {vault, shouldCreate, tokenProgram} = await _getAssociatedTokenAddressAndShouldCreate(
         raffleProvider.provider,
         paymentOption.paymentMint,
         raffle.gatekeeper,
@@ -471,14 +830,19 @@ export const loadPaymentOption = async (
             name: paymentOption.name,
             paymentAmount: paymentOption.paymentAmount,
             ticketsPerPayment: paymentOption.ticketsPerPayment,
+            weightBps: paymentOption.weightBps ?? DEFAULT_TICKET_WEIGHT_BPS,
+            endPaymentAmount: paymentOption.endPaymentAmount ?? new anchor.BN(0),
+            priceSteps: paymentOption.priceSteps ?? [],
+            maxPurchases: paymentOption.maxPurchases ?? new anchor.BN(0),
         },
         {
             accounts: {
                 raffle: raffle.raffle,
                 gatekeeper: raffle.gatekeeper,
+                paymentMint: paymentOption.paymentMint,
                 paymentVault: vault,
                 owner: raffle.owner,
-                tokenProgram: spl.TOKEN_PROGRAM_ID,
+                tokenProgram: tokenProgram,
             },
             signers: [],
             instructions: [
@@ -488,6 +852,7 @@ export const loadPaymentOption = async (
                     raffle.gatekeeper,
                     raffle.owner,
                     shouldCreate,
+                    tokenProgram,
                 )
             ],
         }
@@ -504,7 +869,7 @@ export const loadReward = async (
     const raffleProgram = raffleProvider.raffleProgram;
     const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
 
-    const {vault, shouldCreate} = await _getAssociatedTokenAddressAndShouldCreate(
+    const {vault, shouldCreate, tokenProgram} = await _getAssociatedTokenAddressAndShouldCreate(
         raffleProvider.provider,
         reward.rewardMint,
         raffle.gatekeeper,
@@ -514,6 +879,8 @@ export const loadReward = async (
     const ownerVault = await _getAssociatedTokenAddress(
         reward.rewardMint,
         raffle.owner,
+        false,
+        tokenProgram,
     );
 
     await raffleProgram.rpc.loadReward(
@@ -521,15 +888,17 @@ export const loadReward = async (
             name: reward.name,
             groupId: reward.groupId,
             rewardAmount: reward.rewardAmount,
+            tier: reward.tier ?? 0,
         },
         {
             accounts: {
                 raffle: raffle.raffle,
                 gatekeeper: raffle.gatekeeper,
+                rewardMint: reward.rewardMint,
                 rewardVault: vault,
                 ownerVault: ownerVault,
                 owner: raffle.owner,
-                tokenProgram: spl.TOKEN_PROGRAM_ID,
+                tokenProgram: tokenProgram,
             },
             signers: [],
             instructions: [
@@ -539,6 +908,7 @@ export const loadReward = async (
                     raffle.gatekeeper,
                     raffle.owner,
                     shouldCreate,
+                    tokenProgram,
                 )
             ],
         }
@@ -547,69 +917,67 @@ export const loadReward = async (
     return await raffleProvider.getRaffleAccount(raffle, true);
 }
 
-export interface RaffleFile {
-    raffleName: string,
-    maxTicketsPerHolder: number,
-    maxHolderCount: number,
-    paymentOptions: RafflePaymentOption[],
-    rewards: RaffleReward[],
-}
-export const createRaffleFromFile = async (
+// Token Metadata moves the NFT into the gatekeeper's vault, creating it if needed
+export const loadPnftReward = async (
     raffleProvider: RaffleProvider, 
-    masterRaffleAccount: anchor.web3.PublicKey | MasterRaffleAccount,
-    file: RaffleFile,
-    raffleKeypair?: anchor.web3.Keypair,
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    reward: RaffleReward,
 ) => {
-    const masterRaffle = await raffleProvider.getMasterRaffleAccount(masterRaffleAccount);
-
-    let raffle = await createRaffleAccount(
-        raffleProvider,
-        masterRaffle,
-        file.paymentOptions,
-        file.rewards,
-        file.raffleName,
-        file.maxHolderCount,
-        file.maxTicketsPerHolder,
-        raffleKeypair,
-    );
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
 
-    for(var i = 0; i < file.paymentOptions.length; i++){
-        raffle = await loadPaymentOption(
-            raffleProvider,
-            raffle,
-            file.paymentOptions[i],
-        );
-    }
+    const rewardVault = await _getAssociatedTokenAddress(reward.rewardMint, raffle.gatekeeper, true);
+    const ownerVault = await _getAssociatedTokenAddress(reward.rewardMint, raffle.owner);
 
-    for(var i = 0; i < file.rewards.length; i++){
-        raffle = await loadReward(
-            raffleProvider,
-            raffle,
-            file.rewards[i],
-        );
-    }
+    await raffleProgram.rpc.loadPnftReward(
+        {
+            name: reward.name,
+            groupId: reward.groupId,
+            rewardAmount: new anchor.BN(1),
+            tier: reward.tier ?? 0,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
+                rewardMint: reward.rewardMint,
+                rewardVault: rewardVault,
+                ownerVault: ownerVault,
+                pnft: await _getPnftAccounts(raffleProvider.provider, reward.rewardMint, ownerVault, rewardVault),
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [
+                // Token Metadata's transfer needs more than the default compute budget
+                _getComputeBudgetInstruction(),
+            ],
+        }
+    );
 
-    return await raffleProvider.getRaffleAccount(raffle);
+    return await raffleProvider.getRaffleAccount(raffle, true);
 }
 
-export const startRaffle = async (
+export const loadSolReward = async (
     raffleProvider: RaffleProvider, 
     raffleAccount: anchor.web3.PublicKey | RaffleAccount,
-    endDate: Date,
+    reward: RaffleReward,
 ) => {
     const raffleProgram = raffleProvider.raffleProgram;
     const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
-    const date = _dateToSolanaDate(endDate);
-    const owner = raffleProvider.provider.wallet;
 
-    await raffleProgram.rpc.startRaffle(
+    await raffleProgram.rpc.loadSolReward(
         {
-            endDate: date,
+            name: reward.name,
+            groupId: reward.groupId,
+            rewardAmount: reward.rewardAmount,
+            tier: reward.tier ?? 0,
         },
         {
             accounts: {
                 raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
                 owner: raffle.owner,
+                systemProgram: anchor.web3.SystemProgram.programId,
             },
             signers: [],
             instructions: [],
@@ -619,272 +987,1794 @@ export const startRaffle = async (
     return await raffleProvider.getRaffleAccount(raffle, true);
 }
 
-
-export const requestRNG = async (
+export const loadPotReward = async (
     raffleProvider: RaffleProvider, 
-    masterRaffleAccount: anchor.web3.PublicKey | MasterRaffleAccount,
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    reward: RaffleReward,
 ) => {
     const raffleProgram = raffleProvider.raffleProgram;
-    const switchboardProgram = raffleProvider.switchboardProgram;
-    const masterRaffle = await raffleProvider.getMasterRaffleAccount(masterRaffleAccount);
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
 
-    const [programStateAccount] = ProgramStateAccount.fromSeed(switchboardProgram as any);
-    const switchTokenMint = await programStateAccount.getTokenMint();
-    const {vault, shouldCreate} = await _getAssociatedTokenAddressAndShouldCreate(
+    await raffleProgram.rpc.loadPotReward(
+        {
+            name: reward.name,
+            groupId: reward.groupId,
+            paymentMint: reward.rewardMint,
+            potShareBps: reward.potShareBps,
+            tier: reward.tier ?? 0,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const loadJackpotReward = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    reward: RaffleReward,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+
+    await raffleProgram.rpc.loadJackpotReward(
+        {
+            name: reward.name,
+            tier: reward.tier ?? 0,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                masterRaffle: raffle.masterRaffle,
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+// Splits ticket sales between wallets, shares are in bps and add up to 10000, only before the raffle starts
+export const updatePayees = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    payees: RafflePayee[],
+    dustRecipient?: anchor.web3.PublicKey,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+
+    await raffleProgram.rpc.updatePayees(
+        {
+            payees: payees,
+            dustRecipient: dustRecipient ?? (payees.length > 0 ? payees[0].recipient : raffle.owner),
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const updateSponsors = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    sponsors: anchor.web3.PublicKey[],
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+
+    await raffleProgram.rpc.updateSponsors(
+        {
+            sponsors: sponsors,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+// Adds a reward to a running raffle, can be called by the owner or an allowlisted sponsor
+export const addReward = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    reward: RaffleReward,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const sponsor = raffleProvider.provider.wallet;
+
+    const {vault, shouldCreate, tokenProgram} = await _getAssociatedTokenAddressAndShouldCreate(
         raffleProvider.provider,
-        switchTokenMint.publicKey,
-        masterRaffle.owner,
+        reward.rewardMint,
+        raffle.gatekeeper,
+        true,
     )
 
-    const balance = await raffleProvider.provider.connection.getTokenAccountBalance(vault);
-    if (!balance.value.uiAmount || balance.value.uiAmount < 0.1 || shouldCreate) {
-        throw new Error(
-           "Onwer needs at least 0.1 wSol: to do this call 'spl-token wrap 1' If there is an error, call spl-token unwrap [Account] and try again"
-        );
-    }
+    const sponsorVault = await _getAssociatedTokenAddress(
+        reward.rewardMint,
+        sponsor.publicKey,
+        false,
+        tokenProgram,
+    );
 
-    const { vrfAccount, vrfData } = await getVRFAccount(
+    await raffleProgram.rpc.addReward(
+        {
+            name: reward.name,
+            groupId: reward.groupId,
+            rewardAmount: reward.rewardAmount,
+            tier: reward.tier ?? 0,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
+                rewardMint: reward.rewardMint,
+                rewardVault: vault,
+                sponsorVault: sponsorVault,
+                sponsor: sponsor.publicKey,
+                owner: raffle.owner,
+                tokenProgram: tokenProgram,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            },
+            signers: [],
+            instructions: [
+                ..._getCreateAssociatedTokenAddressInstructions(
+                    reward.rewardMint,
+                    vault,
+                    raffle.gatekeeper,
+                    sponsor.publicKey,
+                    shouldCreate,
+                    tokenProgram,
+                )
+            ],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export interface RaffleFile {
+    raffleName: string,
+    maxTicketsPerHolder: number,
+    maxHolderCount: number,
+    maxSponsorCount?: number,
+    maxFreeTickets?: number,
+    maxRerolls?: number,
+    rerollWindow?: number,
+    claimWindow?: number,
+    maxTotalTickets?: number,
+    paymentOptions: RafflePaymentOption[],
+    rewards: RaffleReward[],
+}
+export const createRaffleFromFile = async (
+    raffleProvider: RaffleProvider, 
+    masterRaffleAccount: anchor.web3.PublicKey | MasterRaffleAccount,
+    file: RaffleFile,
+) => {
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(masterRaffleAccount);
+
+    let raffle = await createRaffleAccount(
         raffleProvider,
         masterRaffle,
-    )
-
-    const queueAccount = new OracleQueueAccount({
-        program: vrfAccount.program,
-        publicKey: vrfData.oracleQueue,
-    });
+        file.paymentOptions,
+        file.rewards,
+        file.raffleName,
+        file.maxHolderCount,
+        file.maxTicketsPerHolder,
+        file.maxSponsorCount,
+        file.maxFreeTickets,
+        file.maxRerolls,
+        file.rerollWindow,
+        file.claimWindow,
+        file.maxTotalTickets,
+    );
 
-    const queue = await queueAccount.loadData();
-    const queueAuthority = queue.authority;
-    const dataBuffer = queue.dataBuffer;
-    const escrow = vrfData.escrow;
-    const [stateAccount, stateBump] = ProgramStateAccount.fromSeed(vrfAccount.program,);
-    const [permissionAccount, permissionBump] = PermissionAccount.fromSeed(vrfAccount.program, queueAuthority, queueAccount.publicKey, vrfAccount.publicKey);
-    try {
-        await permissionAccount.loadData();
+    for(var i = 0; i < file.paymentOptions.length; i++){
+        raffle = await loadPaymentOption(
+            raffleProvider,
+            raffle,
+            file.paymentOptions[i],
+        );
     }
-    catch (_) {
-        throw new Error("A requested permission pda account has not been initialized.");
+
+    for(var i = 0; i < file.rewards.length; i++){
+        if( _isSolReward(file.rewards[i]) ){
+            raffle = await loadSolReward(
+                raffleProvider,
+                raffle,
+                file.rewards[i],
+            );
+        } else if( _isPotReward(file.rewards[i]) ){
+            raffle = await loadPotReward(
+                raffleProvider,
+                raffle,
+                file.rewards[i],
+            );
+        } else if( _isJackpotReward(file.rewards[i]) ){
+            raffle = await loadJackpotReward(
+                raffleProvider,
+                raffle,
+                file.rewards[i],
+            );
+        } else if( _isPnftReward(file.rewards[i]) ){
+            raffle = await loadPnftReward(
+                raffleProvider,
+                raffle,
+                file.rewards[i],
+            );
+        } else {
+            raffle = await loadReward(
+                raffleProvider,
+                raffle,
+                file.rewards[i],
+            );
+        }
     }
-    const tokenProgram = spl.TOKEN_PROGRAM_ID;
-    const recentBlockhashes = anchor.web3.SYSVAR_RECENT_BLOCKHASHES_PUBKEY;
 
-    await vrfAccount.program.rpc.vrfRequestRandomness({
-        stateBump,
-        permissionBump,
-    }, {
-        accounts: {
-            authority: masterRaffle.owner,
-            vrf: vrfAccount.publicKey,
-            oracleQueue: queueAccount.publicKey,
-            queueAuthority,
-            dataBuffer,
-            permission: permissionAccount.publicKey,
-            escrow,
-            payerWallet: vault,
-            payerAuthority: masterRaffle.owner,
-            recentBlockhashes,
-            programState: stateAccount.publicKey,
-            tokenProgram,
+    return await raffleProvider.getRaffleAccount(raffle);
+}
+
+export const startRaffle = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    endDate: Date,
+    startDate?: Date,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const date = _dateToSolanaDate(endDate);
+
+    await raffleProgram.rpc.startRaffle(
+        {
+            startDate: startDate ? _dateToSolanaDate(startDate) : null,
+            endDate: date,
         },
-        signers: [],
-    });
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const updateEndDate = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    endDate: Date,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const date = _dateToSolanaDate(endDate);
+
+    await raffleProgram.rpc.updateEndDate(
+        {
+            endDate: date,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const pauseRaffle = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+
+    await raffleProgram.rpc.pauseRaffle(
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const resumeRaffle = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    extendEndDate?: boolean,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+
+    await raffleProgram.rpc.resumeRaffle(
+        {
+            extendEndDate: extendEndDate ?? true,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const pauseMasterRaffle = async (
+    raffleProvider: RaffleProvider, 
+    masterRaffleAccount: anchor.web3.PublicKey | MasterRaffleAccount,
+    paused: boolean,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(masterRaffleAccount);
+
+    await raffleProgram.rpc.pauseMasterRaffle(
+        {
+            paused: paused,
+        },
+        {
+            accounts: {
+                masterRaffle: masterRaffle.masterRaffle,
+                owner: masterRaffle.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getMasterRaffleAccount(masterRaffle, true);
+}
+
+export const requestRNG = async (
+    raffleProvider: RaffleProvider, 
+    masterRaffleAccount: anchor.web3.PublicKey | MasterRaffleAccount,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const switchboardProgram = raffleProvider.switchboardProgram;
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(masterRaffleAccount);
+
+    const [programStateAccount] = ProgramStateAccount.fromSeed(switchboardProgram as any);
+    const switchTokenMint = await programStateAccount.getTokenMint();
+    const {vault, shouldCreate} = await _getAssociatedTokenAddressAndShouldCreate(
+        raffleProvider.provider,
+        switchTokenMint.publicKey,
+        masterRaffle.owner,
+    )
+
+    const balance = await raffleProvider.provider.connection.getTokenAccountBalance(vault);
+    if (!balance.value.uiAmount || balance.value.uiAmount < 0.1 || shouldCreate) {
+        throw new Error(
+           "Onwer needs at least 0.1 wSol: to do this call 'spl-token wrap 1' If there is an error, call spl-token unwrap [Account] and try again"
+        );
+    }
+
+    const { vrfAccount, vrfData } = await getVRFAccount(
+        raffleProvider,
+        masterRaffle,
+    )
+
+    const queueAccount = new OracleQueueAccount({
+        program: vrfAccount.program,
+        publicKey: vrfData.oracleQueue,
+    });
+
+    const queue = await queueAccount.loadData();
+    const queueAuthority = queue.authority;
+    const dataBuffer = queue.dataBuffer;
+    const escrow = vrfData.escrow;
+    const [stateAccount, stateBump] = ProgramStateAccount.fromSeed(vrfAccount.program,);
+    const [permissionAccount, permissionBump] = PermissionAccount.fromSeed(vrfAccount.program, queueAuthority, queueAccount.publicKey, vrfAccount.publicKey);
+    try {
+        await permissionAccount.loadData();
+    }
+    catch (_) {
+        throw new Error("A requested permission pda account has not been initialized.");
+    }
+    const tokenProgram = spl.TOKEN_PROGRAM_ID;
+    const recentBlockhashes = anchor.web3.SYSVAR_RECENT_BLOCKHASHES_PUBKEY;
+
+    await vrfAccount.program.rpc.vrfRequestRandomness({
+        stateBump,
+        permissionBump,
+    }, {
+        accounts: {
+            authority: masterRaffle.owner,
+            vrf: vrfAccount.publicKey,
+            oracleQueue: queueAccount.publicKey,
+            queueAuthority,
+            dataBuffer,
+            permission: permissionAccount.publicKey,
+            escrow,
+            payerWallet: vault,
+            payerAuthority: masterRaffle.owner,
+            recentBlockhashes,
+            programState: stateAccount.publicKey,
+            tokenProgram,
+        },
+        signers: [],
+    });
+
+    return await raffleProvider.getMasterRaffleAccount(masterRaffle, true);
+}
+
+export const buyTickets = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    paymentOptionIndex: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const holder = raffleProvider.provider.wallet;
+
+    if(paymentOptionIndex >= raffle.paymentOptions.length){
+        throw Error(`Payment option index out of bounds: ${paymentOptionIndex}:${raffle.paymentOptions.length}`);
+    }
+
+    const paymentMint = raffle.paymentOptions[paymentOptionIndex].paymentMint;
+    const tokenProgram = await _getTokenProgram(raffleProvider.provider, paymentMint);
+
+    let paymentVault = await _getAssociatedTokenAddress(
+        paymentMint,
+        raffle.gatekeeper,
+        true,
+        tokenProgram,
+    )
+
+    let holderVault = await _getAssociatedTokenAddress(
+        paymentMint,
+        holder.publicKey,
+        false,
+        tokenProgram,
+    )
+
+    await raffleProgram.rpc.buyTickets(
+        {
+            paymentOptionIndex: paymentOptionIndex,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                masterRaffle: raffle.masterRaffle,
+                gatekeeper: raffle.gatekeeper,
+                paymentMint: paymentMint,
+                paymentVault: paymentVault,
+                holderVault: holderVault,
+                holder: holder.publicKey,
+                owner: raffle.owner,
+                tokenProgram: tokenProgram,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export interface TicketGrant {
+    holder: anchor.web3.PublicKey,
+    tickets: number,
+}
+
+// Signed by the owner or the master's operator, holders don't need to sign
+export const grantTickets = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    grants: TicketGrant[],
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+
+    await raffleProgram.rpc.grantTickets(
+        {
+            grants: grants,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                masterRaffle: raffle.masterRaffle,
+                authority: raffleProvider.provider.wallet.publicKey,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+// What the voucher authority signs, e.g. nacl.sign.detached(getVoucherMessage(...), authority.secretKey)
+export const getVoucherMessage = (
+    raffle: anchor.web3.PublicKey,
+    holder: anchor.web3.PublicKey,
+    tickets: number,
+    nonce: number | anchor.BN,
+) => {
+    return Buffer.concat([
+        Buffer.from(VOUCHER_SEED),
+        raffle.toBuffer(),
+        holder.toBuffer(),
+        Buffer.from([tickets]),
+        new anchor.BN(nonce).toArrayLike(Buffer, "le", 8),
+    ]);
+}
+
+export const getVoucherBitmapAddress = (
+    raffle: anchor.web3.PublicKey,
+    nonce: number | anchor.BN,
+) => {
+    const [voucherBitmap] = anchor.utils.publicKey.findProgramAddressSync(
+        [
+            Buffer.from(VOUCHER_SEED),
+            raffle.toBytes(),
+            new anchor.BN(nonce).divn(VOUCHERS_PER_BITMAP).toArrayLike(Buffer, "le", 8),
+        ],
+        RAFFLE_ID,
+    );
+    return voucherBitmap;
+}
+
+export const redeemVoucher = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    tickets: number,
+    nonce: number | anchor.BN,
+    signature: Uint8Array,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(raffle.masterRaffle);
+    const holder = raffleProvider.provider.wallet;
+
+    await raffleProgram.rpc.redeemVoucher(
+        {
+            tickets: tickets,
+            nonce: new anchor.BN(nonce),
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                masterRaffle: raffle.masterRaffle,
+                voucherBitmap: getVoucherBitmapAddress(raffle.raffle, nonce),
+                instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+                holder: holder.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            },
+            signers: [],
+            instructions: [
+                // Has to be right before redeemVoucher
+                anchor.web3.Ed25519Program.createInstructionWithPublicKey({
+                    publicKey: masterRaffle.voucherAuthority.toBytes(),
+                    message: getVoucherMessage(raffle.raffle, holder.publicKey, tickets, nonce),
+                    signature: signature,
+                }),
+            ],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+// Only the owner's wallet can choose a rewardIndex, otherwise the first reward without a winner is drawn
+export const pickWinner = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    rewardIndex?: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+
+    await raffleProgram.rpc.pickWinner(
+        {
+            rewardIndex: rewardIndex ?? null,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                masterRaffle: raffle.masterRaffle,
+                rngBot: raffle.rngBot,
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+// Anyone can call this once the winner's reroll window is over, needs fresh RNG like pickWinner
+// Only the owner can pick the reward, otherwise the first one that can be rerolled is drawn again
+export const rerollReward = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    rewardIndex?: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+
+    await raffleProgram.rpc.rerollReward(
+        {
+            rewardIndex: rewardIndex ?? null,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                rngBot: raffle.rngBot,
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+// Anyone can call this, splits what is owed for the option's mint between the payees (or pays the owner if there are none)
+export const distributeProceeds = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    paymentOptionIndex: number,
+    amount?: anchor.BN,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const payer = raffleProvider.provider.wallet;
+
+    if(paymentOptionIndex >= raffle.paymentOptions.length){
+        throw Error(`Payment option index out of bounds: ${paymentOptionIndex}:${raffle.paymentOptions.length}`);
+    }
+
+    const paymentMint = raffle.paymentOptions[paymentOptionIndex].paymentMint;
+    const tokenProgram = await _getTokenProgram(raffleProvider.provider, paymentMint);
+    let paymentVault = await _getAssociatedTokenAddress(
+        paymentMint,
+        raffle.gatekeeper,
+        true,
+        tokenProgram,
+    )
+
+    // Every payee's vault in order, the caller pays for the ones that don't exist yet
+    const recipients = raffle.payees.length > 0 ? raffle.payees.map((payee) => payee.recipient) : [raffle.owner];
+    let remainingAccounts = [];
+    let instructions = [];
+    for(let i = 0; i < recipients.length; i++){
+        const {vault, shouldCreate} = await _getAssociatedTokenAddressAndShouldCreate(
+            raffleProvider.provider,
+            paymentMint,
+            recipients[i],
+        )
+
+        remainingAccounts.push({ pubkey: vault, isSigner: false, isWritable: true });
+        instructions.push(
+            ..._getCreateAssociatedTokenAddressInstructions(
+                paymentMint,
+                vault,
+                recipients[i],
+                payer.publicKey,
+                shouldCreate,
+                tokenProgram,
+            )
+        );
+    }
+
+    // Part of the sales go to the master's jackpot on the first withdrawal
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(raffle.masterRaffle);
+    if( raffle.jackpotBps > 0 && masterRaffle.jackpotMint.equals(paymentMint) && !raffle.withdrawals.some((withdrawal) => withdrawal.mint.equals(paymentMint)) ){
+        remainingAccounts.push({
+            pubkey: await _getAssociatedTokenAddress(masterRaffle.jackpotMint, masterRaffle.jackpotKeeper, true, tokenProgram),
+            isSigner: false,
+            isWritable: true,
+        });
+    }
+
+    await raffleProgram.rpc.distributeProceeds(
+        {
+            amount: amount ?? null,
+        },
+        {
+            remainingAccounts: remainingAccounts,
+            accounts: {
+                raffle: raffle.raffle,
+                masterRaffle: raffle.masterRaffle,
+                gatekeeper: raffle.gatekeeper,
+                paymentMint: paymentMint,
+                paymentVault: paymentVault,
+                tokenProgram: tokenProgram,
+            },
+            signers: [],
+            instructions: instructions,
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+// Sends the owner anything in a gatekeeper vault the raffle does not owe anyone, like tokens sent there by mistake
+export const sweepVault = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    mint: anchor.web3.PublicKey,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+
+    const {vault, shouldCreate, tokenProgram} = await _getAssociatedTokenAddressAndShouldCreate(
+        raffleProvider.provider,
+        mint,
+        raffle.owner,
+    )
+
+    await raffleProgram.rpc.sweepVault(
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
+                mint: mint,
+                vault: await _getAssociatedTokenAddress(mint, raffle.gatekeeper, true, tokenProgram),
+                ownerVault: vault,
+                owner: raffle.owner,
+                tokenProgram: tokenProgram,
+            },
+            signers: [],
+            instructions: [
+                ..._getCreateAssociatedTokenAddressInstructions(
+                    mint,
+                    vault,
+                    raffle.owner,
+                    raffle.owner,
+                    shouldCreate,
+                    tokenProgram,
+                )
+            ],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const redeemReward = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    rewardIndex: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const winner = raffleProvider.provider.wallet;
+
+    if(rewardIndex >= raffle.rewards.length){
+        throw Error(`Reward index out of bounds: ${rewardIndex}:${raffle.rewards.length}`);
+    }
+
+    if( _isSolReward(raffle.rewards[rewardIndex]) ){
+        return await redeemSolReward(raffleProvider, raffle, rewardIndex);
+    }
+
+    if( _isJackpotReward(raffle.rewards[rewardIndex]) ){
+        return await redeemJackpot(raffleProvider, raffle, rewardIndex);
+    }
+
+    if( _isPnftReward(raffle.rewards[rewardIndex]) ){
+        return await redeemPnftReward(raffleProvider, raffle, rewardIndex);
+    }
+
+    let {vault, shouldCreate, tokenProgram} = await _getAssociatedTokenAddressAndShouldCreate(
+        raffleProvider.provider,
+        raffle.rewards[rewardIndex].rewardMint,
+        winner.publicKey
+    )
+
+    let rewardVault = await _getAssociatedTokenAddress(
+        raffle.rewards[rewardIndex].rewardMint,
+        raffle.gatekeeper,
+        true,
+        tokenProgram,
+    )
+
+    await raffleProgram.rpc.redeemReward(
+        {
+            rewardIndex: rewardIndex,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
+                rewardMint: raffle.rewards[rewardIndex].rewardMint,
+                rewardVault: rewardVault,
+                winnerVault: vault,
+                winner: winner.publicKey,
+                owner: raffle.owner,
+                tokenProgram: tokenProgram,
+            },
+            signers: [],
+            instructions: [
+                ..._getCreateAssociatedTokenAddressInstructions(
+                    raffle.rewards[rewardIndex].rewardMint,
+                    vault,
+                    winner.publicKey,
+                    winner.publicKey,
+                    shouldCreate,
+                    tokenProgram,
+                )
+            ],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+// Token Metadata creates the winner's vault and token record, paid by the winner
+export const redeemPnftReward = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    rewardIndex: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const winner = raffleProvider.provider.wallet;
+    const reward = raffle.rewards[rewardIndex];
+
+    const rewardVault = await _getAssociatedTokenAddress(reward.rewardMint, raffle.gatekeeper, true);
+    const winnerVault = await _getAssociatedTokenAddress(reward.rewardMint, winner.publicKey);
+
+    await raffleProgram.rpc.redeemPnftReward(
+        {
+            rewardIndex: rewardIndex,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
+                rewardMint: reward.rewardMint,
+                rewardVault: rewardVault,
+                winnerVault: winnerVault,
+                pnft: await _getPnftAccounts(raffleProvider.provider, reward.rewardMint, rewardVault, winnerVault),
+                winner: winner.publicKey,
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [
+                _getComputeBudgetInstruction(),
+            ],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+// Redeems every SPL and pot share reward the wallet won in one instruction, programmable NFTs need redeemReward
+export const redeemRewards = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount, true);
+    const winner = raffleProvider.provider.wallet;
+
+    let remainingAccounts = [];
+    let instructions = [];
+    let createdVaults: string[] = [];
+    let tokenPrograms: anchor.web3.PublicKey[] = [];
+    let rewardIndexes: number[] = [];
+    for(let i = 0; i < raffle.rewards.length; i++){
+        const reward = raffle.rewards[i];
+        if( !reward.winner.equals(winner.publicKey) || reward.rewardRedeemed ){ continue; }
+        if( _isSolReward(reward) || _isJackpotReward(reward) || _isPnftReward(reward) ){ continue; }
+
+        let {vault, shouldCreate, tokenProgram} = await _getAssociatedTokenAddressAndShouldCreate(
+            raffleProvider.provider,
+            reward.rewardMint,
+            winner.publicKey
+        )
+
+        tokenPrograms.push(tokenProgram);
+        rewardIndexes.push(i);
+        remainingAccounts.push(
+            { pubkey: reward.rewardMint, isSigner: false, isWritable: false },
+            { pubkey: await _getAssociatedTokenAddress(reward.rewardMint, raffle.gatekeeper, true, tokenProgram), isSigner: false, isWritable: true },
+            { pubkey: vault, isSigner: false, isWritable: true },
+        );
+
+        if( shouldCreate && !createdVaults.includes(vault.toString()) ){
+            createdVaults.push(vault.toString());
+            instructions.push(
+                ..._getCreateAssociatedTokenAddressInstructions(
+                    reward.rewardMint,
+                    vault,
+                    winner.publicKey,
+                    winner.publicKey,
+                    true,
+                    tokenProgram,
+                )
+            );
+        }
+    }
+
+    // One instruction only has one token program, mixed SPL Token and Token-2022 wins are redeemed one at a time
+    if( tokenPrograms.some((tokenProgram) => !tokenProgram.equals(tokenPrograms[0])) ){
+        for(let i = 0; i < rewardIndexes.length; i++){
+            await redeemReward(raffleProvider, raffle, rewardIndexes[i]);
+        }
+
+        return await raffleProvider.getRaffleAccount(raffle, true);
+    }
+
+    await raffleProgram.rpc.redeemRewards(
+        {
+            remainingAccounts: remainingAccounts,
+            accounts: {
+                raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
+                winner: winner.publicKey,
+                owner: raffle.owner,
+                tokenProgram: tokenPrograms[0] ?? spl.TOKEN_PROGRAM_ID,
+            },
+            signers: [],
+            instructions: instructions,
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+// Owner only, once the claim window is over
+export const reclaimExpiredReward = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    rewardIndex: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(raffle.masterRaffle);
+    const reward = raffle.rewards[rewardIndex];
+
+    if( _isPnftReward(reward) ){
+        return await reclaimExpiredPnftReward(raffleProvider, raffle, rewardIndex);
+    }
+
+    // SOL goes straight to the owner and a jackpot goes back into the pool, neither needs vaults
+    // unless the master has moved its jackpot to another mint, then the owner gets the old one
+    const staleJackpot = _isJackpotReward(reward) && !reward.rewardMint.equals(masterRaffle.jackpotMint);
+    let rewardVault = raffle.gatekeeper;
+    let ownerVault = raffle.owner;
+    let tokenProgram = spl.TOKEN_PROGRAM_ID;
+    let instructions = [];
+    if( (!_isSolReward(reward) && !_isJackpotReward(reward)) || staleJackpot ){
+        let {vault, shouldCreate, tokenProgram: mintTokenProgram} = await _getAssociatedTokenAddressAndShouldCreate(
+            raffleProvider.provider,
+            reward.rewardMint,
+            raffle.owner
+        )
+
+        tokenProgram = mintTokenProgram;
+        rewardVault = await _getAssociatedTokenAddress(reward.rewardMint, staleJackpot ? masterRaffle.jackpotKeeper : raffle.gatekeeper, true, tokenProgram);
+        ownerVault = vault;
+        instructions = _getCreateAssociatedTokenAddressInstructions(
+            reward.rewardMint,
+            vault,
+            raffle.owner,
+            raffle.owner,
+            shouldCreate,
+            tokenProgram,
+        );
+    }
+
+    await raffleProgram.rpc.reclaimExpiredReward(
+        {
+            rewardIndex: rewardIndex,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                masterRaffle: raffle.masterRaffle,
+                gatekeeper: raffle.gatekeeper,
+                jackpotKeeper: masterRaffle.jackpotKeeper,
+                rewardMint: reward.rewardMint,
+                rewardVault: rewardVault,
+                ownerVault: ownerVault,
+                owner: raffle.owner,
+                tokenProgram: tokenProgram,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            },
+            signers: [],
+            instructions: instructions,
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const reclaimExpiredPnftReward = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    rewardIndex: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const reward = raffle.rewards[rewardIndex];
+
+    const rewardVault = await _getAssociatedTokenAddress(reward.rewardMint, raffle.gatekeeper, true);
+    const ownerVault = await _getAssociatedTokenAddress(reward.rewardMint, raffle.owner);
+
+    await raffleProgram.rpc.reclaimExpiredPnftReward(
+        {
+            rewardIndex: rewardIndex,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
+                rewardMint: reward.rewardMint,
+                rewardVault: rewardVault,
+                ownerVault: ownerVault,
+                pnft: await _getPnftAccounts(raffleProvider.provider, reward.rewardMint, rewardVault, ownerVault),
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [
+                _getComputeBudgetInstruction(),
+            ],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+// Anyone can call this, rewards go to the winner's vault and the caller gets the crank fee
+export const distributeReward = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    rewardIndex: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount, true);
+    const reward = raffle.rewards[rewardIndex];
+    const winner = reward.winner;
+
+    if( _isSolReward(reward) ){
+        return await distributeSolReward(raffleProvider, raffle, rewardIndex);
+    }
+
+    if( _isJackpotReward(reward) ){
+        return await distributeJackpot(raffleProvider, raffle, rewardIndex);
+    }
+
+    if( _isPnftReward(reward) ){
+        return await distributePnftReward(raffleProvider, raffle, rewardIndex);
+    }
+
+    const tokenProgram = await _getTokenProgram(raffleProvider.provider, reward.rewardMint);
+
+    await raffleProgram.rpc.distributeReward(
+        {
+            rewardIndex: rewardIndex,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
+                rewardMint: reward.rewardMint,
+                rewardVault: await _getAssociatedTokenAddress(reward.rewardMint, raffle.gatekeeper, true, tokenProgram),
+                winner: winner,
+                winnerVault: await _getAssociatedTokenAddress(reward.rewardMint, winner, true, tokenProgram),
+                payer: raffleProvider.provider.wallet.publicKey,
+                owner: raffle.owner,
+                tokenProgram: tokenProgram,
+                associatedTokenProgram: spl.ASSOCIATED_TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+                rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+// The caller pays for the winner's vault and token record
+export const distributePnftReward = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    rewardIndex: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount, true);
+    const reward = raffle.rewards[rewardIndex];
+    const winner = reward.winner;
+
+    const rewardVault = await _getAssociatedTokenAddress(reward.rewardMint, raffle.gatekeeper, true);
+    const winnerVault = await _getAssociatedTokenAddress(reward.rewardMint, winner, true);
+
+    await raffleProgram.rpc.distributePnftReward(
+        {
+            rewardIndex: rewardIndex,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
+                rewardMint: reward.rewardMint,
+                rewardVault: rewardVault,
+                winner: winner,
+                winnerVault: winnerVault,
+                pnft: await _getPnftAccounts(raffleProvider.provider, reward.rewardMint, rewardVault, winnerVault),
+                payer: raffleProvider.provider.wallet.publicKey,
+                owner: raffle.owner,
+            },
+            signers: [],
+            instructions: [
+                _getComputeBudgetInstruction(),
+            ],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const distributeSolReward = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    rewardIndex: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount, true);
+    const reward = raffle.rewards[rewardIndex];
+
+    await raffleProgram.rpc.distributeSolReward(
+        {
+            rewardIndex: rewardIndex,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
+                winner: reward.winner,
+                payer: raffleProvider.provider.wallet.publicKey,
+                owner: raffle.owner,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const distributeJackpot = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    rewardIndex: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount, true);
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(raffle.masterRaffle);
+    const reward = raffle.rewards[rewardIndex];
+    const winner = reward.winner;
+
+    const tokenProgram = await _getTokenProgram(raffleProvider.provider, reward.rewardMint);
+
+    await raffleProgram.rpc.distributeJackpot(
+        {
+            rewardIndex: rewardIndex,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                masterRaffle: raffle.masterRaffle,
+                gatekeeper: raffle.gatekeeper,
+                jackpotKeeper: masterRaffle.jackpotKeeper,
+                jackpotMint: reward.rewardMint,
+                jackpotVault: await _getAssociatedTokenAddress(reward.rewardMint, masterRaffle.jackpotKeeper, true, tokenProgram),
+                winner: winner,
+                winnerVault: await _getAssociatedTokenAddress(reward.rewardMint, winner, true, tokenProgram),
+                payer: raffleProvider.provider.wallet.publicKey,
+                owner: raffle.owner,
+                tokenProgram: tokenProgram,
+                associatedTokenProgram: spl.ASSOCIATED_TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+                rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const fundCrankFee = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    crankFee: number | anchor.BN,
+    amount: number | anchor.BN,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+
+    await raffleProgram.rpc.fundCrankFee(
+        {
+            crankFee: new anchor.BN(crankFee),
+            amount: new anchor.BN(amount),
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
+                owner: raffle.owner,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const reclaimCrankFee = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+
+    await raffleProgram.rpc.reclaimCrankFee(
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
+                owner: raffle.owner,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const redeemSolReward = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    rewardIndex?: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const winner = raffleProvider.provider.wallet;
+
+    await raffleProgram.rpc.redeemSolReward(
+        {
+            rewardIndex: rewardIndex ?? null,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                gatekeeper: raffle.gatekeeper,
+                winner: winner.publicKey,
+                owner: raffle.owner,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const redeemJackpot = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    rewardIndex?: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(raffle.masterRaffle);
+    const winner = raffleProvider.provider.wallet;
+
+    let {vault, shouldCreate, tokenProgram} = await _getAssociatedTokenAddressAndShouldCreate(
+        raffleProvider.provider,
+        masterRaffle.jackpotMint,
+        winner.publicKey
+    )
+
+    let jackpotVault = await _getAssociatedTokenAddress(
+        masterRaffle.jackpotMint,
+        masterRaffle.jackpotKeeper,
+        true,
+        tokenProgram,
+    )
+
+    await raffleProgram.rpc.redeemJackpot(
+        {
+            rewardIndex: rewardIndex ?? null,
+        },
+        {
+            accounts: {
+                raffle: raffle.raffle,
+                masterRaffle: raffle.masterRaffle,
+                jackpotKeeper: masterRaffle.jackpotKeeper,
+                jackpotMint: masterRaffle.jackpotMint,
+                jackpotVault: jackpotVault,
+                winnerVault: vault,
+                winner: winner.publicKey,
+                owner: raffle.owner,
+                tokenProgram: tokenProgram,
+            },
+            signers: [],
+            instructions: [
+                ..._getCreateAssociatedTokenAddressInstructions(
+                    masterRaffle.jackpotMint,
+                    vault,
+                    winner.publicKey,
+                    winner.publicKey,
+                    shouldCreate,
+                    tokenProgram,
+                )
+            ],
+        }
+    );
+
+    return await raffleProvider.getRaffleAccount(raffle, true);
+}
+
+export const removeRaffleFromMaster = async (
+    raffleProvider: RaffleProvider, 
+    masterRaffleAccount: anchor.web3.PublicKey | MasterRaffleAccount,
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(masterRaffleAccount);
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+
+    if (!await getIsRaffleCleared( raffleProvider, raffle)) {
+        throw Error("This raffle is not cleared");
+    }
+
+    await raffleProgram.rpc.removeRafflesFromMaster(
+        {
+            rafflesToRemove: [
+                raffle.raffle,
+            ]
+        },
+        {
+            accounts: {
+                masterRaffle: masterRaffle.masterRaffle,
+                owner: masterRaffle.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
+
+    return await raffleProvider.getMasterRaffleAccount(masterRaffle, true);
+}
+
+export const configureJackpot = async (
+    raffleProvider: RaffleProvider,
+    masterRaffleAccount: anchor.web3.PublicKey | MasterRaffleAccount,
+    jackpotMint: anchor.web3.PublicKey,
+    jackpotBps: number,
+    jackpotOddsBps: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(masterRaffleAccount);
+    const jackpotKeeper = getJackpotKeeperAddress(masterRaffle.masterRaffle);
+
+    const {vault, shouldCreate, tokenProgram} = await _getAssociatedTokenAddressAndShouldCreate(
+        raffleProvider.provider,
+        jackpotMint,
+        jackpotKeeper,
+        true,
+    )
+
+    await raffleProgram.rpc.configureJackpot(
+        {
+            jackpotBps: jackpotBps,
+            jackpotOddsBps: jackpotOddsBps,
+        },
+        {
+            accounts: {
+                masterRaffle: masterRaffle.masterRaffle,
+                jackpotKeeper: jackpotKeeper,
+                jackpotMint: jackpotMint,
+                jackpotVault: vault,
+                owner: masterRaffle.owner,
+            },
+            signers: [],
+            instructions: [
+                ..._getCreateAssociatedTokenAddressInstructions(
+                    jackpotMint,
+                    vault,
+                    jackpotKeeper,
+                    masterRaffle.owner,
+                    shouldCreate,
+                    tokenProgram,
+                )
+            ],
+        }
+    );
 
     return await raffleProvider.getMasterRaffleAccount(masterRaffle, true);
 }
 
-export const buyTickets = async (
-    raffleProvider: RaffleProvider, 
-    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
-    paymentOptionIndex: number,
+// Leaving a key out keeps the current one, pass PublicKey.default to remove it
+export const updateOperator = async (
+    raffleProvider: RaffleProvider,
+    masterRaffleAccount: anchor.web3.PublicKey | MasterRaffleAccount,
+    operator?: anchor.web3.PublicKey,
+    voucherAuthority?: anchor.web3.PublicKey,
 ) => {
     const raffleProgram = raffleProvider.raffleProgram;
-    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
-    const holder = raffleProvider.provider.wallet;
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(masterRaffleAccount, true);
 
-    if(paymentOptionIndex >= raffle.paymentOptions.length){
-        throw Error(`Payment option index out of bounds: ${paymentOptionIndex}:${raffle.paymentOptions.length}`);
-    }
+    await raffleProgram.rpc.updateOperator(
+        {
+            operator: operator ?? masterRaffle.operator,
+            voucherAuthority: voucherAuthority ?? masterRaffle.voucherAuthority,
+        },
+        {
+            accounts: {
+                masterRaffle: masterRaffle.masterRaffle,
+                owner: masterRaffle.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
 
-    let paymentVault = await _getAssociatedTokenAddress(
-        raffle.paymentOptions[paymentOptionIndex].paymentMint,
-        raffle.gatekeeper,
-        true
-    )
+    return await raffleProvider.getMasterRaffleAccount(masterRaffle, true);
+}
 
-    let holderVault = await _getAssociatedTokenAddress(
-        raffle.paymentOptions[paymentOptionIndex].paymentMint,
-        holder.publicKey,
-    )
+// --------- TEMPLATES -----------------------------------------
+// Creates the template and loads its payment options and rewards, fund it by sending the rewards to the keeper's vaults
+export const createTemplate = async (
+    raffleProvider: RaffleProvider,
+    masterRaffleAccount: anchor.web3.PublicKey | MasterRaffleAccount,
+    paymentOptions: RafflePaymentOption[],
+    rewards: RaffleReward[],
+    duration: number,
+    templateName?: string,
+    maxHolderCount?: number,
+    maxTicketsPerHolder?: number,
+    index?: number,
+    claimWindow?: number,
+    maxTotalTickets?: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(masterRaffleAccount);
+    const template = getTemplateAddress(masterRaffle.masterRaffle, index ?? 0);
+    const owner = raffleProvider.provider.wallet;
 
-    await raffleProgram.rpc.buyTickets(
+    await raffleProgram.rpc.createTemplate(
         {
-            paymentOptionIndex: paymentOptionIndex,
+            index: new anchor.BN(index ?? 0),
+            name: templateName ?? "SOLAPE Raffle",
+            maxTicketsPerHolder: maxTicketsPerHolder ?? 0xFF,
+            paymentOptionCount: paymentOptions.length,
+            rewardCount: rewards.length,
+            maxHolderCount: maxHolderCount ?? 200,
+            duration: new anchor.BN(duration),
+            claimWindow: new anchor.BN(claimWindow ?? DEFAULT_CLAIM_WINDOW),
+            maxTotalTickets: maxTotalTickets ?? 0,
         },
         {
             accounts: {
-                raffle: raffle.raffle,
-                gatekeeper: raffle.gatekeeper,
-                paymentVault: paymentVault,
-                holderVault: holderVault,
-                holder: holder.publicKey,
-                owner: raffle.owner,
-                tokenProgram: spl.TOKEN_PROGRAM_ID,
+                template: template,
+                keeper: getTemplateKeeperAddress(template),
+                masterRaffle: masterRaffle.masterRaffle,
+                owner: owner.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
             },
             signers: [],
             instructions: [],
         }
     );
 
-    return await raffleProvider.getRaffleAccount(raffle, true);
+    for(let i = 0; i < paymentOptions.length; i++){
+        await loadTemplatePaymentOption(raffleProvider, template, paymentOptions[i]);
+    }
+
+    for(let i = 0; i < rewards.length; i++){
+        await loadTemplateReward(raffleProvider, template, rewards[i]);
+    }
+
+    return await raffleProvider.getTemplateAccount(template, true);
 }
 
-export const pickWinner = async (
-    raffleProvider: RaffleProvider, 
-    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+export const loadTemplatePaymentOption = async (
+    raffleProvider: RaffleProvider,
+    templateAccount: anchor.web3.PublicKey | RaffleTemplateAccount,
+    paymentOption: RafflePaymentOption,
 ) => {
     const raffleProgram = raffleProvider.raffleProgram;
-    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const template = await raffleProvider.getTemplateAccount(templateAccount);
 
-    await raffleProgram.rpc.pickWinner(
+    await raffleProgram.rpc.loadTemplatePaymentOption(
+        {
+            name: paymentOption.name,
+            paymentAmount: paymentOption.paymentAmount,
+            ticketsPerPayment: paymentOption.ticketsPerPayment,
+            weightBps: paymentOption.weightBps ?? DEFAULT_TICKET_WEIGHT_BPS,
+            endPaymentAmount: paymentOption.endPaymentAmount ?? new anchor.BN(0),
+            priceSteps: paymentOption.priceSteps ?? [],
+            maxPurchases: paymentOption.maxPurchases ?? new anchor.BN(0),
+        },
         {
             accounts: {
-                raffle: raffle.raffle,
-                rngBot: raffle.rngBot,
-                owner: raffle.owner,
+                template: template.template,
+                paymentMint: paymentOption.paymentMint,
+                owner: template.owner,
             },
             signers: [],
             instructions: [],
         }
     );
 
-    return await raffleProvider.getRaffleAccount(raffle, true);
+    return await raffleProvider.getTemplateAccount(template, true);
 }
 
-export const cashOutOwner = async (
-    raffleProvider: RaffleProvider, 
-    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
-    paymentOptionIndex: number,
+export const loadTemplateReward = async (
+    raffleProvider: RaffleProvider,
+    templateAccount: anchor.web3.PublicKey | RaffleTemplateAccount,
+    reward: RaffleReward,
 ) => {
     const raffleProgram = raffleProvider.raffleProgram;
-    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
-    const owner = raffleProvider.provider.wallet;
-
-    if(paymentOptionIndex >= raffle.paymentOptions.length){
-        throw Error(`Payment option index out of bounds: ${paymentOptionIndex}:${raffle.paymentOptions.length}`);
-    }
+    const template = await raffleProvider.getTemplateAccount(templateAccount);
 
-    let paymentVault = await _getAssociatedTokenAddress(
-        raffle.paymentOptions[paymentOptionIndex].paymentMint,
-        raffle.gatekeeper,
-        true
+    const {vault, shouldCreate, tokenProgram} = await _getAssociatedTokenAddressAndShouldCreate(
+        raffleProvider.provider,
+        reward.rewardMint,
+        template.keeper,
+        true,
     )
 
-    let ownerVault = await _getAssociatedTokenAddress(
-        raffle.paymentOptions[paymentOptionIndex].paymentMint,
-        owner.publicKey,
-    )
+    await raffleProgram.rpc.loadTemplateReward(
+        {
+            name: reward.name,
+            groupId: reward.groupId,
+            rewardAmount: reward.rewardAmount,
+            tier: reward.tier ?? 0,
+        },
+        {
+            accounts: {
+                template: template.template,
+                keeper: template.keeper,
+                rewardMint: reward.rewardMint,
+                templateVault: vault,
+                owner: template.owner,
+            },
+            signers: [],
+            instructions: [
+                ..._getCreateAssociatedTokenAddressInstructions(
+                    reward.rewardMint,
+                    vault,
+                    template.keeper,
+                    template.owner,
+                    shouldCreate,
+                    tokenProgram,
+                )
+            ],
+        }
+    );
+
+    return await raffleProvider.getTemplateAccount(template, true);
+}
+
+export const updateTemplate = async (
+    raffleProvider: RaffleProvider,
+    templateAccount: anchor.web3.PublicKey | RaffleTemplateAccount,
+    active: boolean,
+    duration?: number,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const template = await raffleProvider.getTemplateAccount(templateAccount);
 
-    await raffleProgram.rpc.cashOutOwner(
+    await raffleProgram.rpc.updateTemplate(
+        {
+            active: active,
+            duration: duration ? new anchor.BN(duration) : template.duration,
+        },
         {
             accounts: {
-                raffle: raffle.raffle,
-                gatekeeper: raffle.gatekeeper,
-                paymentVault: paymentVault,
-                ownerVault: ownerVault,
-                owner: raffle.owner,
-                tokenProgram: spl.TOKEN_PROGRAM_ID,
+                template: template.template,
+                owner: template.owner,
             },
             signers: [],
             instructions: [],
         }
     );
 
-    return await raffleProvider.getRaffleAccount(raffle, true);
+    return await raffleProvider.getTemplateAccount(template, true);
 }
 
-export const redeemReward = async (
-    raffleProvider: RaffleProvider, 
-    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
-    rewardIndex: number,
+// Same split as updatePayees, every round spawned from now on pays out this way
+export const updateTemplatePayees = async (
+    raffleProvider: RaffleProvider,
+    templateAccount: anchor.web3.PublicKey | RaffleTemplateAccount,
+    payees: RafflePayee[],
+    dustRecipient?: anchor.web3.PublicKey,
 ) => {
     const raffleProgram = raffleProvider.raffleProgram;
-    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
-    const winner = raffleProvider.provider.wallet;
+    const template = await raffleProvider.getTemplateAccount(templateAccount);
 
-    if(rewardIndex >= raffle.rewards.length){
-        throw Error(`Reward index out of bounds: ${rewardIndex}:${raffle.rewards.length}`);
-    }
+    await raffleProgram.rpc.updateTemplatePayees(
+        {
+            payees: payees,
+            dustRecipient: dustRecipient ?? (payees.length > 0 ? payees[0].recipient : template.owner),
+        },
+        {
+            accounts: {
+                template: template.template,
+                owner: template.owner,
+            },
+            signers: [],
+            instructions: [],
+        }
+    );
 
-    let rewardVault = await _getAssociatedTokenAddress(
-        raffle.rewards[rewardIndex].rewardMint,
-        raffle.gatekeeper,
-        true
-    )
+    return await raffleProvider.getTemplateAccount(template, true);
+}
+
+export const withdrawTemplateFunds = async (
+    raffleProvider: RaffleProvider,
+    templateAccount: anchor.web3.PublicKey | RaffleTemplateAccount,
+    mint: anchor.web3.PublicKey,
+    amount: anchor.BN,
+) => {
+    const raffleProgram = raffleProvider.raffleProgram;
+    const template = await raffleProvider.getTemplateAccount(templateAccount);
 
-    let {vault, shouldCreate} = await _getAssociatedTokenAddressAndShouldCreate(
+    const {vault, shouldCreate, tokenProgram} = await _getAssociatedTokenAddressAndShouldCreate(
         raffleProvider.provider,
-        raffle.rewards[rewardIndex].rewardMint,
-        winner.publicKey
+        mint,
+        template.owner,
     )
+    const templateVault = await _getAssociatedTokenAddress(mint, template.keeper, true, tokenProgram);
 
-    await raffleProgram.rpc.redeemReward(
+    await raffleProgram.rpc.withdrawTemplateFunds(
+        {
+            amount: amount,
+        },
         {
             accounts: {
-                raffle: raffle.raffle,
-                gatekeeper: raffle.gatekeeper,
-                rewardVault: rewardVault,
-                winnerVault: vault,
-                winner: winner.publicKey,
-                owner: raffle.owner,
-                tokenProgram: spl.TOKEN_PROGRAM_ID,
+                template: template.template,
+                keeper: template.keeper,
+                mint: mint,
+                templateVault: templateVault,
+                ownerVault: vault,
+                owner: template.owner,
+                tokenProgram: tokenProgram,
             },
             signers: [],
             instructions: [
                 ..._getCreateAssociatedTokenAddressInstructions(
-                    raffle.rewards[rewardIndex].rewardMint,
+                    mint,
                     vault,
-                    winner.publicKey,
-                    winner.publicKey,
+                    template.owner,
+                    template.owner,
                     shouldCreate,
+                    tokenProgram,
                 )
             ],
         }
     );
 
-    return await raffleProvider.getRaffleAccount(raffle, true);
+    return await raffleProvider.getTemplateAccount(template, true);
 }
 
-
-export const removeRaffleFromMaster = async (
-    raffleProvider: RaffleProvider, 
-    masterRaffleAccount: anchor.web3.PublicKey | MasterRaffleAccount,
-    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+// Anyone can call this once the previous round has ended, the caller pays for the new raffle
+export const spawnFromTemplate = async (
+    raffleProvider: RaffleProvider,
+    templateAccount: anchor.web3.PublicKey | RaffleTemplateAccount,
 ) => {
     const raffleProgram = raffleProvider.raffleProgram;
-    const masterRaffle = await raffleProvider.getMasterRaffleAccount(masterRaffleAccount);
-    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const template = await raffleProvider.getTemplateAccount(templateAccount, true);
+    const masterRaffle = await raffleProvider.getMasterRaffleAccount(template.masterRaffle);
+    const raffle = getRaffleAddress(masterRaffle.masterRaffle, masterRaffle.raffleCounter);
+    const gatekeeper = getGatekeeperAddress(raffle);
+    const payer = raffleProvider.provider.wallet;
+
+    // The new gatekeeper's reward vaults have to exist before the rewards are moved in
+    let remainingAccounts = [];
+    let instructions = [];
+    let tokenProgram = spl.TOKEN_PROGRAM_ID;
+    for(let i = 0; i < template.rewards.length; i++){
+        const rewardMint = template.rewards[i].rewardMint;
+        const rewardTokenProgram = await _getTokenProgram(raffleProvider.provider, rewardMint);
+        if( i > 0 && !rewardTokenProgram.equals(tokenProgram) ){
+            throw Error("Every template reward has to be under the same token program");
+        }
+        tokenProgram = rewardTokenProgram;
 
-    if (!await getIsRaffleCleared( raffleProvider, raffle)) {
-        throw Error("This raffle is not cleared");
+        const rewardVault = await _getAssociatedTokenAddress(rewardMint, gatekeeper, true, tokenProgram);
+
+        remainingAccounts.push(
+            { pubkey: rewardMint, isSigner: false, isWritable: false },
+            { pubkey: await _getAssociatedTokenAddress(rewardMint, template.keeper, true, tokenProgram), isSigner: false, isWritable: true },
+            { pubkey: rewardVault, isSigner: false, isWritable: true },
+        );
+
+        let isNewVault = true;
+        for(let j = 0; j < i; j++){
+            if( template.rewards[j].rewardMint.equals(rewardMint) ){ isNewVault = false; }
+        }
+
+        if( isNewVault ){
+            instructions.push(
+                ..._getCreateAssociatedTokenAddressInstructions(
+                    rewardMint,
+                    rewardVault,
+                    gatekeeper,
+                    payer.publicKey,
+                    true,
+                    tokenProgram,
+                )
+            );
+        }
     }
 
-    await raffleProgram.rpc.removeRafflesFromMaster(
-        {
-            rafflesToRemove: [
-                raffle.raffle,
-            ]
-        },
+    await raffleProgram.rpc.spawnFromTemplate(
         {
+            remainingAccounts: remainingAccounts,
             accounts: {
+                template: template.template,
+                keeper: template.keeper,
+                raffle: raffle,
+                gatekeeper: gatekeeper,
                 masterRaffle: masterRaffle.masterRaffle,
-                owner: masterRaffle.owner,
+                rngBot: masterRaffle.rngBot,
+                payer: payer.publicKey,
+                tokenProgram: tokenProgram,
+                systemProgram: anchor.web3.SystemProgram.programId,
             },
             signers: [],
-            instructions: [],
+            instructions: instructions,
         }
     );
 
-    return await raffleProvider.getMasterRaffleAccount(masterRaffle, true);
+    return await raffleProvider.getRaffleAccount(raffle, true);
 }
 
 // Just to be able to update the oracle
@@ -930,28 +2820,65 @@ export const raffleToString = async (
     string += "PAYMENTS: " + raffle.paymentOptions.length + "\n";
     for (var i = 0; i < raffle.paymentOptions.length; i++){
         string += "P: " + raffle.paymentOptions[i].paymentMint.toString() + ": " + raffle.paymentOptions[i].paymentTally + "\n";  
+        string += "A: " + raffle.paymentOptions[i].amountPaid + "\n";  
         string += "C: " + raffle.paymentOptions[i].cashedOut + "\n";  
     }
     string += "REWARDS: " + raffle.rewards.length + "\n";
     for (var i = 0; i < raffle.rewards.length; i++){
         string += "R: " + raffle.rewards[i].rewardMint.toString() + ": " + raffle.rewards[i].rewardAmount + "\n";  
         string += "GI: " + raffle.rewards[i].groupId.toString() + "\n";  
+        string += "T: " + raffle.rewards[i].tier + "\n";  
         string += "C: " + raffle.rewards[i].rewardRedeemed + "\n";  
     }
 
     return (string);
 }
 
+// Same as the program, the price of one purchase at unixTime (now by default)
+export const getTicketPrice = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    paymentOptionIndex: number,
+    unixTime?: number,
+) => {
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const option = raffle.paymentOptions[paymentOptionIndex];
+    const time = new anchor.BN(unixTime ?? Math.floor(Date.now() / 1000));
+    let price = option.paymentAmount;
+
+    if( option.priceSteps.length > 0 ){
+        for( var i = 0; i < option.priceSteps.length; i++){
+            if( time.gte(option.priceSteps[i].startsAt) ){
+                price = option.priceSteps[i].paymentAmount;
+            }
+        }
+    } else if( !option.endPaymentAmount.isZero() && raffle.rampEndDate.gt(raffle.startDate) ){
+        const elapsed = anchor.BN.min(anchor.BN.max(time, raffle.startDate), raffle.rampEndDate).sub(raffle.startDate);
+        price = option.paymentAmount.add(
+            option.endPaymentAmount.sub(option.paymentAmount).mul(elapsed).div(raffle.rampEndDate.sub(raffle.startDate))
+        );
+    }
+
+    return price;
+}
+
+// Odds of the next draw, tickets are weighted by the payment option they were bought with
 export const getHolderChances = async (
     raffleProvider: RaffleProvider, 
     raffleAccount: anchor.web3.PublicKey | RaffleAccount,
 ) => {
     const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    let entries = 0;
+    let holderEntries = 0;
 
-    return (
-        await getHolderTicketCount(raffleProvider, raffle) / 
-        await getTotalTicketCount(raffleProvider, raffle)
-    );
+    for( var i = 0; i < raffle.holders.length; i++){
+        entries += raffle.holders[i].entries;
+        if( raffle.holders[i].holder.toString() == raffleProvider.provider.wallet.publicKey.toString() ){
+            holderEntries = raffle.holders[i].entries;
+        }
+    }
+
+    return entries == 0 ? 0 : holderEntries / entries;
 }
 
 export const getTotalTicketCount = async (
@@ -983,6 +2910,18 @@ export const getHolderTicketCount = async (
     return 0;
 }
 
+// After this the owner can redeem whatever winners have not
+// Each winner gets the full claim window from when they were picked, or from the end date before the draw
+export const getClaimDeadline = async (
+    raffleProvider: RaffleProvider, 
+    raffleAccount: anchor.web3.PublicKey | RaffleAccount,
+    rewardIndex?: number,
+) => {
+    const raffle = await raffleProvider.getRaffleAccount(raffleAccount);
+    const pickedAt = rewardIndex === undefined ? new anchor.BN(0) : (raffle.rewards[rewardIndex].pickedAt ?? new anchor.BN(0));
+    return new Date(anchor.BN.max(raffle.endDate, pickedAt).add(raffle.claimWindow).toNumber() * 1000);
+}
+
 export const getIsRaffleCleared = async (
     raffleProvider: RaffleProvider, 
     raffleAccount: anchor.web3.PublicKey | RaffleAccount,
@@ -1052,21 +2991,21 @@ export const getVRFAccount = async (
     return { vrfAccount, vrfData };
 }
 
-const _masterRaffleAccountSize = (maxRaffles: number) => {
-    return MASTER_RAFFLE_ACCOUNT_BASE_SIZE + (maxRaffles * 32);
+const _isSolReward = (reward: RaffleReward) => {
+    return reward.kind && reward.kind.lamports !== undefined;
 }
 
-const _raffleAccountSize = (
-    paymentOptions: number,
-    rewards: number,
-    maxHolders: number,
-) => {
-    return RAFFLE_ACCOUNT_BASE_SIZE + 
-    (paymentOptions * RAFFLE_PAYMENT_OPTION_SIZE) +
-    (rewards * RAFFLE_REWARD_SIZE) +
-    (maxHolders * RAFFLE_TICKET_HOLDER_SIZE);
+const _isPotReward = (reward: RaffleReward) => {
+    return reward.kind && reward.kind.potShare !== undefined;
 }
 
+const _isJackpotReward = (reward: RaffleReward) => {
+    return reward.kind && reward.kind.jackpot !== undefined;
+}
+
+const _isPnftReward = (reward: RaffleReward) => {
+    return reward.kind && reward.kind.programmableNft !== undefined;
+}
 
 const _dateToSolanaDate = (date: Date) => {
     return new anchor.BN(Math.floor(date.getTime() / 1000));
@@ -1077,30 +3016,33 @@ const _getProgram = async (provider: anchor.Provider, programID: anchor.web3.Pub
     return new anchor.Program<anchor.Idl>(idl as any, programID, provider);
 }
 
-const _getSPLAccount = async (provider: anchor.Provider, mint: anchor.web3.PublicKey, vault: anchor.web3.PublicKey) => {
-    return new spl.Token(provider.connection, mint, spl.TOKEN_PROGRAM_ID, anchor.web3.Keypair.generate()).getAccountInfo(vault);
+// The mint's owner is the token program its vaults live under, SPL Token or Token-2022
+const _getTokenProgram = async (provider: anchor.Provider, mint: anchor.web3.PublicKey) => {
+    const mintInfo = await provider.connection.getAccountInfo(mint);
+    if( !mintInfo ){
+        throw Error(`Mint not found: ${mint.toString()}`);
+    }
+
+    return mintInfo.owner;
 }
 
-const _getAssociatedTokenAddress = async (mint: anchor.web3.PublicKey, owner: anchor.web3.PublicKey, allowOffCurve?: boolean) => {
+const _getAssociatedTokenAddress = async (mint: anchor.web3.PublicKey, owner: anchor.web3.PublicKey, allowOffCurve?: boolean, tokenProgram?: anchor.web3.PublicKey) => {
     return spl.Token.getAssociatedTokenAddress(
         spl.ASSOCIATED_TOKEN_PROGRAM_ID,
-        spl.TOKEN_PROGRAM_ID,
+        tokenProgram ?? spl.TOKEN_PROGRAM_ID,
         mint,
         owner,
         allowOffCurve
     );
 }
 
+// Token-2022 vaults can be larger than spl-token expects, so only check that the vault exists
 const _getAssociatedTokenAddressAndShouldCreate = async (provider: anchor.Provider, mint: anchor.web3.PublicKey, owner: anchor.web3.PublicKey, allowOffCurve?: boolean) => {
-    let vault = await _getAssociatedTokenAddress( mint, owner, allowOffCurve );
-    let shouldCreate = false;
-    try {
-        await _getSPLAccount(provider, mint, vault);
-    } catch (e) {
-        shouldCreate = true;
-    }
+    const tokenProgram = await _getTokenProgram(provider, mint);
+    let vault = await _getAssociatedTokenAddress( mint, owner, allowOffCurve, tokenProgram );
+    let shouldCreate = (await provider.connection.getAccountInfo(vault)) === null;
 
-    return {vault, shouldCreate};
+    return {vault, shouldCreate, tokenProgram};
 }
 
 const _getCreateAssociatedTokenAddressInstructions = (
@@ -1108,12 +3050,13 @@ const _getCreateAssociatedTokenAddressInstructions = (
     vault: anchor.web3.PublicKey,
     owner: anchor.web3.PublicKey,
     payer: anchor.web3.PublicKey,
-    shouldCreate?: boolean
+    shouldCreate?: boolean,
+    tokenProgram?: anchor.web3.PublicKey,
 ) => {
     return (shouldCreate ?? true) ? [
         spl.Token.createAssociatedTokenAccountInstruction(
             spl.ASSOCIATED_TOKEN_PROGRAM_ID,
-            spl.TOKEN_PROGRAM_ID,
+            tokenProgram ?? spl.TOKEN_PROGRAM_ID,
             mint,
             vault,
             owner,
@@ -1121,3 +3064,66 @@ const _getCreateAssociatedTokenAddressInstructions = (
         )
     ] : [];
 }
+
+// The rule set sits in the metadata's programmable config, after every variable length field
+const _getPnftRuleSet = async (provider: anchor.Provider, metadata: anchor.web3.PublicKey) => {
+    const metadataInfo = await provider.connection.getAccountInfo(metadata);
+    if( !metadataInfo ){
+        throw Error(`Metadata not found: ${metadata.toString()}`);
+    }
+
+    const data = metadataInfo.data;
+    let offset = 1 + 32 + 32; // key, update authority, mint
+    for(let i = 0; i < 3; i++){ offset += 4 + data.readUInt32LE(offset); } // name, symbol, uri
+    offset += 2; // seller fee bps
+    if( data[offset++] === 1 ){ offset += 4 + data.readUInt32LE(offset) * 34; } // creators
+    offset += 2; // primary sale happened, is mutable
+    if( data[offset++] === 1 ){ offset += 1; } // edition nonce
+    if( data[offset++] === 1 ){ offset += 1; } // token standard
+    if( data[offset++] === 1 ){ offset += 33; } // collection
+    if( data[offset++] === 1 ){ offset += 17; } // uses
+    if( data[offset++] === 1 ){ offset += 9; } // collection details
+    if( offset >= data.length || data[offset++] !== 1 ){ return null; } // programmable config
+    offset += 1; // V1
+    if( data[offset++] !== 1 ){ return null; }
+
+    return new anchor.web3.PublicKey(data.slice(offset, offset + 32));
+}
+
+// Token Metadata accounts for moving a programmable NFT out of source into destination
+const _getPnftAccounts = async (
+    provider: anchor.Provider,
+    mint: anchor.web3.PublicKey,
+    source: anchor.web3.PublicKey,
+    destination: anchor.web3.PublicKey,
+) => {
+    const metadata = getMetadataAddress(mint);
+    const ruleSet = await _getPnftRuleSet(provider, metadata);
+
+    return {
+        metadata: metadata,
+        edition: getEditionAddress(mint),
+        ownerTokenRecord: getTokenRecordAddress(mint, source),
+        destinationTokenRecord: getTokenRecordAddress(mint, destination),
+        authorizationRulesProgram: ruleSet ? TOKEN_AUTH_RULES_PROGRAM_ID : TOKEN_METADATA_PROGRAM_ID,
+        authorizationRules: ruleSet ?? TOKEN_METADATA_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: spl.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: spl.ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+    };
+}
+
+// SetComputeUnitLimit, this version of web3.js has no helper for it
+const _getComputeBudgetInstruction = (units: number = 400000) => {
+    let data = Buffer.alloc(5);
+    data.writeUInt8(2, 0);
+    data.writeUInt32LE(units, 1);
+
+    return new anchor.web3.TransactionInstruction({
+        keys: [],
+        programId: new anchor.web3.PublicKey("ComputeBudget111111111111111111111111111111"),
+        data: data,
+    });
+}